@@ -0,0 +1,73 @@
+use raylib::{
+    core::{drawing::RaylibDraw, text::measure_text, RaylibHandle},
+    ffi::{Color, KeyboardKey},
+};
+
+use crate::meta::GameInfo;
+
+/// Show the description and the authors of the game until Z or X is pressed.
+pub fn about_screen(rl: &RaylibHandle, info: &GameInfo) {
+    let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+
+    let subtitle = if info.creation_date.is_empty() {
+        info.tags.join(", ")
+    } else {
+        format!("{}  -  {}", info.creation_date, info.tags.join(", "))
+    };
+
+    while !rl.window_should_close() {
+        rl.begin_drawing(|d| {
+            d.clear_background(Color::WHITE);
+
+            d.draw_text(
+                &info.title,
+                (w - measure_text(&info.title, 48)) / 2,
+                h / 8,
+                48,
+                Color::BLACK,
+            );
+
+            d.draw_text(
+                &subtitle,
+                (w - measure_text(&subtitle, 20)) / 2,
+                h / 8 + 60,
+                20,
+                Color::GRAY,
+            );
+
+            for (i, line) in info.description.lines().enumerate() {
+                d.draw_text(
+                    line,
+                    (w - measure_text(line, 24)) / 2,
+                    h / 3 + 32 * i as i32,
+                    24,
+                    Color::DARKGRAY,
+                );
+            }
+
+            for (i, author) in info.authors.iter().enumerate() {
+                d.draw_text(
+                    author,
+                    (w - measure_text(author, 20)) / 2,
+                    h * 5 / 8 + 28 * i as i32,
+                    20,
+                    Color::DARKGRAY,
+                );
+            }
+
+            let hint = "Appuyez sur Z ou X pour revenir";
+
+            d.draw_text(
+                hint,
+                (w - measure_text(hint, 20)) / 2,
+                h - 80,
+                20,
+                Color::GRAY,
+            );
+        });
+
+        if rl.is_key_pressed(KeyboardKey::KEY_Z) || rl.is_key_pressed(KeyboardKey::KEY_X) {
+            return;
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Achievements, declared in `assets/achievements.json` and unlocked by what
+//! the players do during their games:
+//!
+//! ```json
+//! [{ "id": "flawless", "name": "Sans faute", "description": "...",
+//!    "trigger": "flawless_stages", "target": 1 }]
+//! ```
+//!
+//! Progress is kept in `achievements_progress.json` next to the high scores,
+//! so it is shared by everyone playing on the cabinet.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+};
+
+use raylib::{
+    core::{drawing::RaylibDrawHandle, text::measure_text},
+    ffi::Color,
+    prelude::RaylibDraw,
+};
+use serde::Deserialize;
+
+use crate::{
+    assets::AssetError,
+    game::events::{GameplayEvent, GameplayEventKind},
+    mods::Mods,
+};
+
+const DEFINITIONS: &str = "achievements.json";
+const PROGRESS_PATH: &str = "achievements_progress.json";
+
+/// Seconds an unlock stays on screen.
+const TOAST_DURATION: f32 = 4.0;
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Trigger {
+    /// Bricks broken, over every game.
+    Bricks,
+    /// Bricks broken by a player without losing a ball.
+    BricksInOneLaunch,
+    /// Paddle hits in a row without losing a ball.
+    Rally,
+    /// Stages cleared without a player losing a life.
+    FlawlessStages,
+    VersusWins,
+}
+
+impl Trigger {
+    /// Whether the progress is the best streak rather than a total.
+    fn is_record(self) -> bool {
+        matches!(self, Trigger::BricksInOneLaunch | Trigger::Rally)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Achievement {
+    id: String,
+    name: String,
+    description: String,
+    trigger: Trigger,
+    target: u32,
+}
+
+/// What a player did since their last lost ball.
+#[derive(Default)]
+struct Streak {
+    bricks: u32,
+    hits: u32,
+    /// Whether the player lost a life during the current stage.
+    lost_in_stage: bool,
+}
+
+struct Toast {
+    name: String,
+    description: String,
+    time_left: f32,
+}
+
+pub struct Achievements {
+    definitions: Vec<Achievement>,
+    /// Progress by achievement id, an achievement is unlocked once it reaches its target.
+    progress: HashMap<String, u32>,
+    streaks: HashMap<usize, Streak>,
+    /// Unlocks waiting to be shown, the first one is on screen.
+    toasts: VecDeque<Toast>,
+}
+
+impl Achievements {
+    /// Read the definitions through the mods and the progress made so far.
+    pub fn load(mods: &Mods) -> Self {
+        let path = mods.asset(DEFINITIONS);
+
+        let definitions = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|err| err.to_string()))
+            .map_err(|reason| eprintln!("{}", AssetError::Manifest { path, reason }))
+            .unwrap_or_default();
+
+        let progress = fs::read_to_string(PROGRESS_PATH)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            definitions,
+            progress,
+            streaks: HashMap::new(),
+            toasts: VecDeque::new(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = serde_json::to_string_pretty(&self.progress)
+            .map_err(|err| err.to_string())
+            .and_then(|content| fs::write(PROGRESS_PATH, content).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            eprintln!("Unable to save the achievements: {err}");
+        }
+    }
+
+    /// Forget the streaks of the previous game.
+    pub fn start_game(&mut self) {
+        self.streaks.clear();
+    }
+
+    /// Count a gameplay event of a human player.
+    pub fn record(&mut self, event: &GameplayEvent) {
+        match (event.kind, event.player) {
+            (GameplayEventKind::BrickDestroyed, Some(player)) => {
+                let streak = self.streaks.entry(player).or_default();
+                streak.bricks += 1;
+                let bricks = streak.bricks;
+
+                self.advance(Trigger::Bricks, 1);
+                self.advance(Trigger::BricksInOneLaunch, bricks);
+            }
+            (GameplayEventKind::PaddleHit, Some(player)) => {
+                let streak = self.streaks.entry(player).or_default();
+                streak.hits += 1;
+                let hits = streak.hits;
+
+                self.advance(Trigger::Rally, hits);
+            }
+            (GameplayEventKind::BallLost, Some(player)) => {
+                let streak = self.streaks.entry(player).or_default();
+                streak.bricks = 0;
+                streak.hits = 0;
+            }
+            (GameplayEventKind::LifeLost, Some(player)) => {
+                self.streaks.entry(player).or_default().lost_in_stage = true;
+            }
+            (GameplayEventKind::StageCleared, _) => {
+                let flawless = !self.streaks.is_empty()
+                    && self.streaks.values().all(|streak| !streak.lost_in_stage);
+
+                // The balls are brought back to the paddles for the next stage.
+                self.streaks.clear();
+
+                if flawless {
+                    self.advance(Trigger::FlawlessStages, 1);
+                }
+            }
+            (GameplayEventKind::MatchWon, _) => self.advance(Trigger::VersusWins, 1),
+            _ => (),
+        }
+    }
+
+    /// Add `value` to the achievements of `trigger` (or keep it as their best
+    /// streak), unlocking those reaching their target.
+    fn advance(&mut self, trigger: Trigger, value: u32) {
+        for achievement in self.definitions.iter().filter(|a| a.trigger == trigger) {
+            let progress = self.progress.entry(achievement.id.clone()).or_default();
+
+            if *progress >= achievement.target {
+                continue;
+            }
+
+            *progress = if trigger.is_record() {
+                (*progress).max(value)
+            } else {
+                *progress + value
+            };
+
+            if *progress >= achievement.target {
+                self.toasts.push_back(Toast {
+                    name: achievement.name.clone(),
+                    description: achievement.description.clone(),
+                    time_left: TOAST_DURATION,
+                });
+            }
+        }
+    }
+
+    pub fn update(&mut self, frame_time: f32) {
+        if let Some(toast) = self.toasts.front_mut() {
+            toast.time_left -= frame_time;
+
+            if toast.time_left <= 0.0 {
+                self.toasts.pop_front();
+            }
+        }
+    }
+
+    /// Draw the unlock being shown at the top of the screen.
+    pub fn draw(&self, d: &RaylibDrawHandle, width: i32) {
+        let Some(toast) = self.toasts.front() else {
+            return;
+        };
+
+        let title = format!("Succès débloqué : {}", toast.name);
+        let box_width = measure_text(&title, 24).max(measure_text(&toast.description, 20)) + 40;
+        let x = (width - box_width) / 2;
+
+        d.draw_rectangle(x, 20, box_width, 70, Color::BLACK.fade(0.8));
+        d.draw_rectangle_lines(x, 20, box_width, 70, Color::GOLD);
+        d.draw_text(&title, x + 20, 30, 24, Color::GOLD);
+        d.draw_text(&toast.description, x + 20, 60, 20, Color::WHITE);
+    }
+}
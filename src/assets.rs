@@ -1,40 +1,252 @@
+//! Sounds and textures, declared in `assets/manifest.json`.
+//!
+//! Files are looked up through the mods, so a mod can replace the manifest as
+//! well as any file it lists. A missing file is reported and replaced: sounds by
+//! silence, textures by a placeholder.
+
+use std::{collections::HashMap, fmt, fs};
+
 use raylib::prelude::*;
+use serde::Deserialize;
+
+use crate::{mods::Mods, theme::Theme};
+
+const MANIFEST: &str = "manifest.json";
+
+/// Textures drawn by the game, looked up by name in the manifest.
+const TEXTURES: [&str; 4] = ["logo_1p", "logo_2p", "logo_raylib", "logo_raylib_rust"];
+
+/// Size of the box drawn in place of a missing texture.
+const PLACEHOLDER_SIZE: i32 = 128;
+
+#[derive(Debug)]
+pub enum AssetError {
+    /// The manifest can't be read or parsed.
+    Manifest { path: String, reason: String },
+    /// The texture isn't declared in the manifest.
+    UnknownTexture(String),
+    /// A file declared in the manifest can't be loaded.
+    Load { path: String, reason: String },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::Manifest { path, reason } => write!(f, "Invalid {path}: {reason}"),
+            AssetError::UnknownTexture(name) => write!(f, "No texture {name} in the manifest"),
+            AssetError::Load { path, reason } => write!(f, "Unable to load {path}: {reason}"),
+        }
+    }
+}
+
+/// Sounds played at random for the same event.
+#[derive(Clone, Copy)]
+pub enum SoundBank {
+    Bounce,
+    Destroyed,
+    /// A hazard is sent to the opponent.
+    Laser,
+    /// A choice is made in a menu.
+    Ui,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct SoundBanks {
+    #[serde(default)]
+    bounce: Vec<String>,
+    #[serde(default)]
+    destroyed: Vec<String>,
+    #[serde(default)]
+    laser: Vec<String>,
+    #[serde(default)]
+    ui: Vec<String>,
+}
+
+/// Files of the assets, by sound bank and texture name.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AssetManifest {
+    #[serde(default)]
+    sounds: SoundBanks,
+    #[serde(default)]
+    textures: HashMap<String, String>,
+}
+
+impl AssetManifest {
+    /// Read the manifest, with every path resolved through the mods. Without a
+    /// valid manifest, the game runs silently with placeholder textures.
+    pub fn load(mods: &Mods) -> Self {
+        let path = mods.asset(MANIFEST);
+
+        let manifest = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<Self>(&content).map_err(|err| err.to_string())
+            })
+            .map_err(|reason| AssetError::Manifest { path, reason });
+
+        let mut manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!("{err}");
+                return Self::default();
+            }
+        };
+
+        let banks = &mut manifest.sounds;
+
+        for file in [
+            &mut banks.bounce,
+            &mut banks.destroyed,
+            &mut banks.laser,
+            &mut banks.ui,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(manifest.textures.values_mut())
+        {
+            *file = mods.asset(file);
+        }
+
+        manifest
+    }
+
+    fn try_texture(&self, rl: &RaylibHandle, name: &str) -> Result<Texture2D, AssetError> {
+        let path = self
+            .textures
+            .get(name)
+            .ok_or_else(|| AssetError::UnknownTexture(name.to_string()))?;
+
+        rl.load_texture(path).map_err(|reason| AssetError::Load {
+            path: path.clone(),
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Load a texture of the manifest, or a placeholder if it can't be.
+    fn texture(&self, rl: &RaylibHandle, name: &str) -> AssetTexture {
+        match self.try_texture(rl, name) {
+            Ok(texture) => AssetTexture::Loaded(texture),
+            Err(err) => {
+                eprintln!("{err}, drawing a placeholder instead");
+                AssetTexture::Placeholder
+            }
+        }
+    }
+}
+
+pub enum AssetTexture {
+    Loaded(Texture2D),
+    /// Box drawn in place of a missing texture.
+    Placeholder,
+}
+
+impl AssetTexture {
+    pub fn width(&self) -> i32 {
+        match self {
+            AssetTexture::Loaded(texture) => texture.as_raw().width,
+            AssetTexture::Placeholder => PLACEHOLDER_SIZE,
+        }
+    }
+
+    pub fn height(&self) -> i32 {
+        match self {
+            AssetTexture::Loaded(texture) => texture.as_raw().height,
+            AssetTexture::Placeholder => PLACEHOLDER_SIZE,
+        }
+    }
+
+    pub fn draw(&self, d: &RaylibDrawHandle, x: i32, y: i32, tint: Color) {
+        match self {
+            AssetTexture::Loaded(texture) => d.draw_texture(texture, x, y, tint),
+            AssetTexture::Placeholder => {
+                let (size, alpha) = (PLACEHOLDER_SIZE, tint.a as f32 / 255.0);
+
+                d.draw_rectangle(x, y, size, size, Color::MAGENTA.fade(alpha));
+                d.draw_rectangle_lines(x, y, size, size, Color::BLACK.fade(alpha));
+                d.draw_line(x, y, x + size, y + size, Color::BLACK.fade(alpha));
+                d.draw_line(x + size, y, x, y + size, Color::BLACK.fade(alpha));
+            }
+        }
+    }
+}
 
 pub struct Assets<'rl> {
-    pub destroyed_sounds: Vec<Sound<'rl>>,
-    pub bounce_sound: Option<Sound<'rl>>,
+    textures: HashMap<&'static str, AssetTexture>,
+    /// Drawn for a texture missing from `TEXTURES`.
+    placeholder: AssetTexture,
+    theme: Theme,
+
+    bounce: Vec<Sound<'rl>>,
+    destroyed: Vec<Sound<'rl>>,
+    laser: Vec<Sound<'rl>>,
+    ui: Vec<Sound<'rl>>,
 }
 
 impl<'rl> Assets<'rl> {
-    pub fn load(raudio: &'rl RaylibAudio) -> Self {
-        // Load as much explosionI.wav as available.
-        let destroyed_sounds = (1..)
-            .map(|i| Sound::load_sound(raudio, format!("assets/explosion{i}.wav").as_str()).ok())
-            .take_while(|r| r.is_some())
-            .map(|o| o.unwrap())
+    /// Load the textures and sound banks of the manifest, with placeholders
+    /// and silence for the files that can't be, and the sprites of `theme`.
+    pub fn load(
+        rl: &RaylibHandle,
+        raudio: &'rl RaylibAudio,
+        mods: &Mods,
+        theme: Option<&str>,
+    ) -> Self {
+        let textures = TEXTURES
+            .into_iter()
+            .map(|name| (name, mods.manifest.texture(rl, name)))
             .collect();
 
-        let bounce_sound = Sound::load_sound(raudio, "assets/bounce.wav").ok();
+        let load_bank = |files: &[String]| {
+            files
+                .iter()
+                .filter_map(|path| {
+                    Sound::load_sound(raudio, path)
+                        .map_err(|reason| AssetError::Load {
+                            path: path.clone(),
+                            reason: reason.to_string(),
+                        })
+                        .map_err(|err| eprintln!("{err}"))
+                        .ok()
+                })
+                .collect()
+        };
+
+        let banks = &mods.manifest.sounds;
 
         Self {
-            destroyed_sounds,
-            bounce_sound,
+            textures,
+            placeholder: AssetTexture::Placeholder,
+            theme: Theme::load(rl, mods, theme),
+            bounce: load_bank(&banks.bounce),
+            destroyed: load_bank(&banks.destroyed),
+            laser: load_bank(&banks.laser),
+            ui: load_bank(&banks.ui),
         }
     }
 
-    pub fn play_destroyed(&self, raudio: &RaylibAudio) {
-        // Play a random explosion.
-        if self.destroyed_sounds.is_empty() {
-            return;
-        }
+    pub fn texture(&self, name: &str) -> &AssetTexture {
+        self.textures.get(name).unwrap_or(&self.placeholder)
+    }
 
-        let sound_index = fastrand::usize(0..self.destroyed_sounds.len());
-        raudio.play_sound(&self.destroyed_sounds[sound_index]);
+    pub fn theme(&self) -> &Theme {
+        &self.theme
     }
 
-    pub fn play_bounce(&self, raudio: &RaylibAudio) {
-        if let Some(bounce_sound) = &self.bounce_sound {
-            raudio.play_sound(bounce_sound);
+    /// Play a random sound of the bank, if it has any.
+    pub fn play(&self, raudio: &RaylibAudio, bank: SoundBank) {
+        let sounds = match bank {
+            SoundBank::Bounce => &self.bounce,
+            SoundBank::Destroyed => &self.destroyed,
+            SoundBank::Laser => &self.laser,
+            SoundBank::Ui => &self.ui,
+        };
+
+        if sounds.is_empty() {
+            return;
         }
+
+        raudio.play_sound(&sounds[fastrand::usize(..sounds.len())]);
     }
 }
@@ -0,0 +1,119 @@
+use raylib::{
+    core::{drawing::RaylibDraw, text::measure_text, RaylibHandle},
+    ffi::Color,
+    prelude::RaylibAudio,
+};
+
+use crate::{
+    assets::Assets,
+    credits::Credits,
+    game::{
+        ai::AiDifficulty,
+        rules::{GameMode, Tuning},
+        Game,
+    },
+    highscores::HighScores,
+    intro,
+    meta::GameInfo,
+    mods::Mods,
+};
+
+/// Seconds without any input on the menu before the attract mode starts.
+pub const ATTRACT_DELAY: f32 = 30.0;
+
+/// Seconds the high scores stay on screen.
+const HIGH_SCORES_DURATION: f32 = 8.0;
+
+/// Seconds of demo gameplay.
+const DEMO_DURATION: f32 = 45.0;
+
+/// Cycle the intro, the high scores and a demo game played by the computer
+/// until a key is pressed or a coin inserted.
+pub fn attract_mode(
+    rl: &RaylibHandle,
+    raudio: &RaylibAudio,
+    assets: &Assets,
+    info: &GameInfo,
+    mods: &Mods,
+    highscores: &HighScores,
+    credits: &mut Credits,
+) {
+    while !rl.window_should_close() {
+        if !intro::intro(rl, info, assets, credits)
+            || !show_high_scores(rl, highscores, credits)
+            || !demo(rl, raudio, assets, mods, credits)
+        {
+            return;
+        }
+    }
+}
+
+/// Returns false if a key was pressed or a coin inserted.
+fn show_high_scores(rl: &RaylibHandle, highscores: &HighScores, credits: &mut Credits) -> bool {
+    let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+    let mut time = 0.0;
+
+    while !rl.window_should_close() && time < HIGH_SCORES_DURATION {
+        rl.begin_drawing(|d| {
+            d.clear_background(Color::WHITE);
+            highscores.draw(&d, w, h);
+        });
+
+        let coin = credits.update(rl);
+
+        if rl.get_key_pressed().is_some() || coin {
+            return false;
+        }
+
+        time += rl.get_frame_time();
+    }
+
+    true
+}
+
+/// Let the computer play a solo game, returns false if a key was pressed or a coin inserted.
+fn demo(
+    rl: &RaylibHandle,
+    raudio: &RaylibAudio,
+    assets: &Assets,
+    mods: &Mods,
+    credits: &mut Credits,
+) -> bool {
+    let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+
+    let mut game = Game::default();
+    game.set_cpu_player(0, AiDifficulty::Hard);
+    game.set_tuning(mods.tuning(Tuning::default()));
+    game.set_levels(mods.levels.clone());
+    game.init(rl, GameMode::Solo);
+
+    let mut time = 0.0;
+
+    while !rl.window_should_close() && !game.is_over() && time < DEMO_DURATION {
+        game.update(rl, raudio, assets);
+
+        rl.begin_drawing(|d| {
+            game.draw(&d, assets);
+
+            let text = "DÉMO - Appuyez sur une touche";
+
+            d.draw_text(
+                text,
+                (w - measure_text(text, 24)) / 2,
+                h - 60,
+                24,
+                Color::DARKGRAY,
+            );
+        });
+
+        let coin = credits.update(rl);
+
+        if rl.get_key_pressed().is_some() || coin {
+            return false;
+        }
+
+        time += rl.get_frame_time();
+    }
+
+    true
+}
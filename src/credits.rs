@@ -0,0 +1,207 @@
+use std::{fs, path::PathBuf};
+
+use raylib::{
+    core::{drawing::RaylibDrawHandle, text::measure_text, RaylibHandle},
+    ffi::{Color, KeyboardKey},
+    prelude::RaylibDraw,
+};
+
+/// Credits requested by the cabinet, set on the command line.
+pub struct CreditSettings {
+    /// Credits needed to start a game, 0 for free play.
+    pub per_game: u32,
+
+    /// Credits needed to continue after a GAME OVER, `None` disables continues.
+    pub continue_cost: Option<u32>,
+
+    /// Key the coin mechanism is wired to.
+    pub coin_key: KeyboardKey,
+
+    /// GPIO value file (e.g. `/sys/class/gpio/gpio17/value`) the coin mechanism
+    /// is wired to, read every frame.
+    pub coin_gpio: Option<PathBuf>,
+}
+
+impl Default for CreditSettings {
+    fn default() -> Self {
+        Self {
+            per_game: 0,
+            continue_cost: None,
+            coin_key: KeyboardKey::KEY_FIVE,
+            coin_gpio: None,
+        }
+    }
+}
+
+impl CreditSettings {
+    /// Take `--credits <per game>`, `--continue <credits>`, `--coin-key <key>`
+    /// and `--coin-gpio <path>` out of `args`.
+    pub fn from_args(args: &mut Vec<String>) -> Self {
+        let mut settings = Self::default();
+        let mut remaining = vec![];
+        let mut iter = std::mem::take(args).into_iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--credits" => match iter.next().and_then(|value| value.parse().ok()) {
+                    Some(per_game) => settings.per_game = per_game,
+                    None => eprintln!("--credits expects a number of credits per game, ignored"),
+                },
+                "--continue" => match iter.next().and_then(|value| value.parse().ok()) {
+                    Some(cost) => settings.continue_cost = Some(cost),
+                    None => eprintln!("--continue expects a number of credits, ignored"),
+                },
+                "--coin-key" => match iter.next().and_then(|value| parse_key(&value)) {
+                    Some(key) => settings.coin_key = key,
+                    None => eprintln!("--coin-key expects a letter or a digit, ignored"),
+                },
+                "--coin-gpio" => match iter.next() {
+                    Some(path) => settings.coin_gpio = Some(path.into()),
+                    None => eprintln!("--coin-gpio expects a path, ignored"),
+                },
+                _ => remaining.push(arg),
+            }
+        }
+
+        *args = remaining;
+        settings
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyboardKey> {
+    use KeyboardKey::*;
+
+    let key = match name.to_ascii_uppercase().as_str() {
+        "0" => KEY_ZERO,
+        "1" => KEY_ONE,
+        "2" => KEY_TWO,
+        "3" => KEY_THREE,
+        "4" => KEY_FOUR,
+        "5" => KEY_FIVE,
+        "6" => KEY_SIX,
+        "7" => KEY_SEVEN,
+        "8" => KEY_EIGHT,
+        "9" => KEY_NINE,
+        "A" => KEY_A,
+        "B" => KEY_B,
+        "C" => KEY_C,
+        "D" => KEY_D,
+        "E" => KEY_E,
+        "F" => KEY_F,
+        "G" => KEY_G,
+        "H" => KEY_H,
+        "I" => KEY_I,
+        "J" => KEY_J,
+        "K" => KEY_K,
+        "L" => KEY_L,
+        "M" => KEY_M,
+        "N" => KEY_N,
+        "O" => KEY_O,
+        "P" => KEY_P,
+        "Q" => KEY_Q,
+        "R" => KEY_R,
+        "S" => KEY_S,
+        "T" => KEY_T,
+        "U" => KEY_U,
+        "V" => KEY_V,
+        "W" => KEY_W,
+        "X" => KEY_X,
+        "Y" => KEY_Y,
+        "Z" => KEY_Z,
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+/// Coins inserted in the cabinet.
+pub struct Credits {
+    settings: CreditSettings,
+    count: u32,
+
+    /// Last value read from the GPIO, coins are counted on rising edges.
+    gpio_high: bool,
+}
+
+impl Credits {
+    pub fn new(settings: CreditSettings) -> Self {
+        Self {
+            settings,
+            count: 0,
+            gpio_high: false,
+        }
+    }
+
+    pub fn free_play(&self) -> bool {
+        self.settings.per_game == 0
+    }
+
+    /// Count the coins inserted since the last frame, returns true if there is any.
+    pub fn update(&mut self, rl: &RaylibHandle) -> bool {
+        let mut coins = rl.is_key_pressed(self.settings.coin_key) as u32;
+
+        if let Some(path) = &self.settings.coin_gpio {
+            let high = fs::read_to_string(path)
+                .map(|value| value.trim() == "1")
+                .unwrap_or_default();
+
+            if high && !self.gpio_high {
+                coins += 1;
+            }
+
+            self.gpio_high = high;
+        }
+
+        self.count += coins;
+        coins > 0
+    }
+
+    /// Pay for a new game, returns false if there isn't enough credits.
+    pub fn start_game(&mut self) -> bool {
+        self.spend(self.settings.per_game)
+    }
+
+    /// Whether continues are enabled.
+    pub fn continues(&self) -> bool {
+        self.settings.continue_cost.is_some()
+    }
+
+    /// Pay for a continue, returns false if continues are disabled or there
+    /// isn't enough credits.
+    pub fn continue_game(&mut self) -> bool {
+        self.settings
+            .continue_cost
+            .is_some_and(|cost| self.spend(cost))
+    }
+
+    fn spend(&mut self, credits: u32) -> bool {
+        if self.free_play() {
+            return true;
+        }
+
+        match self.count.checked_sub(credits) {
+            Some(count) => {
+                self.count = count;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show the credit count at the bottom right of the screen.
+    pub fn draw(&self, d: &RaylibDrawHandle, width: i32, height: i32, color: Color) {
+        let text = if self.free_play() {
+            "Partie libre".to_string()
+        } else {
+            format!("Crédits : {}", self.count)
+        };
+
+        d.draw_text(
+            &text,
+            width - measure_text(&text, 24) - 20,
+            height - 40,
+            24,
+            color,
+        );
+    }
+}
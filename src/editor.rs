@@ -0,0 +1,316 @@
+//! Level editor, started with `--edit <level.txt>`.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use raylib::{
+    core::{drawing::RaylibDraw, text::measure_text, RaylibHandle},
+    ffi::{Color, KeyboardKey, MouseButton},
+    prelude::RaylibAudio,
+};
+
+use crate::{
+    assets::Assets,
+    game::{
+        level::{brick_color, draw_brick_kind, BrickKind, Level, LevelBrick, BRICK_COLORS},
+        rules::{GameMode, Tuning},
+        Game, BRICKS_PER_LINE, BRICK_HEIGHT, INITIAL_DOWN_POSITION, MAX_BRICK_LINES,
+    },
+    mods::Mods,
+};
+
+/// Changes that can be undone.
+const MAX_UNDO: usize = 100;
+
+/// Seconds a status message stays on screen.
+const MESSAGE_DURATION: f32 = 2.0;
+
+const COLOR_KEYS: [KeyboardKey; 10] = [
+    KeyboardKey::KEY_ZERO,
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
+];
+
+type Grid = Vec<Vec<Option<LevelBrick>>>;
+
+/// Take `--edit <level.txt>` out of `args`.
+pub fn path_from_args(args: &mut Vec<String>) -> Option<PathBuf> {
+    let index = args.iter().position(|arg| arg == "--edit")?;
+    args.remove(index);
+
+    if index >= args.len() {
+        eprintln!("--edit expects a level file, ignored");
+        return None;
+    }
+
+    Some(args.remove(index).into())
+}
+
+/// Level to edit, empty if the file doesn't exist yet. The error is set when
+/// the file can't be read, it must not be overwritten then.
+fn load(path: &Path) -> (Level, Option<String>) {
+    let name = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    let parsed = match fs::read_to_string(path) {
+        Ok(text) => Level::parse(&name, &text),
+        // New level
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Level::empty(name.clone())),
+        Err(err) => Err(err.to_string()),
+    };
+
+    let (mut level, error) = match parsed {
+        Ok(level) => (level, None),
+        Err(err) => {
+            eprintln!("Can't edit {}: {err}", path.display());
+            (Level::empty(name), Some(err))
+        }
+    };
+
+    level.script = fs::read_to_string(path.with_extension("rhai")).ok();
+
+    // Make the whole grid editable.
+    level.bricks.resize(MAX_BRICK_LINES, vec![]);
+    level
+        .bricks
+        .iter_mut()
+        .for_each(|line| line.resize(BRICKS_PER_LINE, None));
+
+    (level, error)
+}
+
+pub fn editor(rl: &RaylibHandle, raudio: &RaylibAudio, assets: &Assets, mods: &Mods, path: &Path) {
+    let (mut level, load_error) = load(path);
+    let mut color = 0;
+    let mut kind = BrickKind::Normal;
+
+    let mut undo: Vec<Grid> = vec![];
+    let mut redo: Vec<Grid> = vec![];
+
+    let mut message: Option<(String, f32)> = load_error
+        .as_ref()
+        .map(|err| (format!("Impossible d'ouvrir : {err}"), MESSAGE_DURATION));
+
+    while !rl.window_should_close() {
+        let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+        let brick_width = w as f32 / BRICKS_PER_LINE as f32;
+        let top = INITIAL_DOWN_POSITION - BRICK_HEIGHT / 2.0;
+
+        // Cell under the mouse
+        let (x, y) = (rl.get_mouse_x() as f32, rl.get_mouse_y() as f32);
+        let cell = (x >= 0.0 && y >= top)
+            .then(|| {
+                (
+                    (x / brick_width) as usize,
+                    ((y - top) / BRICK_HEIGHT) as usize,
+                )
+            })
+            .filter(|&(column, row)| column < BRICKS_PER_LINE && row < MAX_BRICK_LINES);
+
+        if let Some((column, row)) = cell {
+            let paint = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+            let erase = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT);
+
+            // A stroke is undone at once.
+            if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+                || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT)
+            {
+                undo.push(level.bricks.clone());
+                redo.clear();
+
+                if undo.len() > MAX_UNDO {
+                    undo.remove(0);
+                }
+            }
+
+            if paint {
+                level.bricks[row][column] = Some(LevelBrick::new(kind, color));
+            } else if erase {
+                level.bricks[row][column] = None;
+            }
+        }
+
+        if let Some(key) = COLOR_KEYS.iter().position(|&key| rl.is_key_pressed(key)) {
+            color = key as u8;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_K) {
+            let index = BrickKind::ALL.iter().position(|&other| other == kind);
+            kind = BrickKind::ALL[index.map_or(0, |index| (index + 1) % BrickKind::ALL.len())];
+        }
+
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_Z) {
+            if let Some(bricks) = undo.pop() {
+                redo.push(std::mem::replace(&mut level.bricks, bricks));
+            }
+        }
+
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_Y) {
+            if let Some(bricks) = redo.pop() {
+                undo.push(std::mem::replace(&mut level.bricks, bricks));
+            }
+        }
+
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_S) {
+            // Never replace a file that couldn't be read, nor save a level that can't be read back.
+            let saved = match &load_error {
+                Some(err) => Err(format!("le fichier illisible n'est pas écrasé ({err})")),
+                None => level
+                    .validate()
+                    .and_then(|()| fs::write(path, level.to_text()).map_err(|err| err.to_string())),
+            };
+
+            let text = match saved {
+                Ok(()) => format!("Niveau enregistré dans {}", path.display()),
+                Err(err) => format!("Impossible d'enregistrer : {err}"),
+            };
+
+            message = Some((text, MESSAGE_DURATION));
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            test_play(rl, raudio, assets, mods, &level);
+        }
+
+        rl.begin_drawing(|d| {
+            d.clear_background(Color::RAYWHITE);
+
+            for (row, line) in level.bricks.iter().enumerate() {
+                for (column, brick) in line.iter().enumerate() {
+                    let (x, y) = (
+                        (column as f32 * brick_width) as i32,
+                        (top + row as f32 * BRICK_HEIGHT) as i32,
+                    );
+
+                    if let Some(brick) = brick {
+                        d.draw_rectangle(
+                            x,
+                            y,
+                            brick_width as i32,
+                            BRICK_HEIGHT as i32,
+                            brick_color(brick.color, column, row),
+                        );
+                        draw_brick_kind(
+                            &d,
+                            brick.kind,
+                            x,
+                            y,
+                            brick_width as i32,
+                            BRICK_HEIGHT as i32,
+                        );
+                    }
+
+                    d.draw_rectangle_lines(
+                        x,
+                        y,
+                        brick_width as i32,
+                        BRICK_HEIGHT as i32,
+                        if cell == Some((column, row)) {
+                            Color::BLACK
+                        } else {
+                            Color::LIGHTGRAY
+                        },
+                    );
+                }
+            }
+
+            // Palette
+            let palette_y = (top + MAX_BRICK_LINES as f32 * BRICK_HEIGHT) as i32 + 30;
+
+            for (i, swatch) in BRICK_COLORS.iter().enumerate() {
+                let x = 20 + 60 * i as i32;
+
+                d.draw_rectangle(x, palette_y, 50, 30, *swatch);
+                d.draw_text(&i.to_string(), x + 20, palette_y + 36, 20, Color::DARKGRAY);
+
+                if i == color as usize {
+                    d.draw_rectangle_lines(x - 3, palette_y - 3, 56, 36, Color::BLACK);
+                }
+            }
+
+            // Kind of the painted bricks
+            let kind_x = 20 + 60 * BRICK_COLORS.len() as i32 + 40;
+
+            d.draw_rectangle(kind_x, palette_y, 50, 30, BRICK_COLORS[color as usize]);
+            draw_brick_kind(&d, kind, kind_x, palette_y, 50, 30);
+            d.draw_text(kind.name(), kind_x + 60, palette_y + 5, 20, Color::DARKGRAY);
+
+            d.draw_text(
+                "Clic gauche : peindre  -  Clic droit : effacer  -  0-9 : couleur  -  K : type",
+                20,
+                h - 70,
+                20,
+                Color::GRAY,
+            );
+            d.draw_text(
+                "Ctrl+Z / Ctrl+Y : annuler / rétablir  -  Ctrl+S : enregistrer  -  T : tester",
+                20,
+                h - 40,
+                20,
+                Color::GRAY,
+            );
+
+            if let Some((text, _)) = &message {
+                d.draw_text(
+                    text,
+                    w - measure_text(text, 20) - 20,
+                    palette_y,
+                    20,
+                    Color::DARKGREEN,
+                );
+            }
+        });
+
+        if let Some((_, time)) = &mut message {
+            *time -= rl.get_frame_time();
+        }
+
+        message = message.filter(|(_, time)| *time > 0.0);
+    }
+}
+
+/// Play the level being edited until backspace is pressed.
+fn test_play(rl: &RaylibHandle, raudio: &RaylibAudio, assets: &Assets, mods: &Mods, level: &Level) {
+    let mut game = Game::default();
+    game.set_tuning(mods.tuning(Tuning::default()));
+    game.set_levels(vec![level.clone()]);
+    game.init(rl, GameMode::Solo);
+
+    while !rl.window_should_close() && !game.is_over() {
+        game.update(rl, raudio, assets);
+
+        rl.begin_drawing(|d| {
+            game.draw(&d, assets);
+
+            let text = "Test - Retour arrière pour revenir à l'éditeur";
+
+            d.draw_text(
+                text,
+                (rl.get_screen_width() - measure_text(text, 20)) / 2,
+                rl.get_screen_height() - 40,
+                20,
+                Color::DARKGRAY,
+            );
+        });
+
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            return;
+        }
+    }
+}
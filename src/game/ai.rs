@@ -0,0 +1,291 @@
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    query::Without,
+    system::{Query, Res},
+};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    components::{Position, Size, Velocity},
+    entities::{ball::Ball, brick::BrickKind, player::Player},
+    resources::ScreenInfo,
+    rules::Tuning,
+};
+
+/// Skill of a computer controlled paddle.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl AiDifficulty {
+    pub const ALL: [AiDifficulty; 3] =
+        [AiDifficulty::Easy, AiDifficulty::Normal, AiDifficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AiDifficulty::Easy => "Facile",
+            AiDifficulty::Normal => "Normal",
+            AiDifficulty::Hard => "Difficile",
+        }
+    }
+
+    /// Frames between two looks at the field.
+    fn reaction_delay(self) -> u32 {
+        match self {
+            AiDifficulty::Easy => 20,
+            AiDifficulty::Normal => 10,
+            AiDifficulty::Hard => 3,
+        }
+    }
+
+    /// Maximum error (in pixels) on the predicted landing point.
+    fn error(self) -> f32 {
+        match self {
+            AiDifficulty::Easy => 90.0,
+            AiDifficulty::Normal => 40.0,
+            AiDifficulty::Hard => 10.0,
+        }
+    }
+
+    /// Whether the paddle tries to send the ball toward the bricks.
+    fn aims(self) -> bool {
+        self != AiDifficulty::Easy
+    }
+}
+
+/// Drives a player paddle in place of `PlayerControls`.
+#[derive(Component)]
+pub struct AiController {
+    pub difficulty: AiDifficulty,
+
+    /// Frames before the next look at the field.
+    timer: u32,
+
+    /// Position the paddle is moving to.
+    target_x: Option<f32>,
+}
+
+impl AiController {
+    pub fn new(difficulty: AiDifficulty) -> Self {
+        Self {
+            difficulty,
+            timer: difficulty.reaction_delay(),
+            target_x: None,
+        }
+    }
+}
+
+/// Fold `x` into `[min, max]` as if it bounced on both sides.
+fn reflect(x: f32, min: f32, max: f32) -> f32 {
+    let span = max - min;
+
+    if span <= 0.0 {
+        return min;
+    }
+
+    let offset = (x - min).rem_euclid(2.0 * span);
+
+    if offset > span {
+        max - (offset - span)
+    } else {
+        min + offset
+    }
+}
+
+/// Predict where and in how many frames a ball reaches the height `target_y`,
+/// bouncing on the side walls and the ceiling.
+fn predict_landing(
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    radius: f32,
+    target_y: f32,
+    width: f32,
+) -> Option<(f32, f32)> {
+    if velocity.y == 0.0 {
+        return None;
+    }
+
+    let distance = if velocity.y > 0.0 {
+        target_y - radius - position.y
+    } else {
+        // Up to the ceiling, then down to the target.
+        (position.y - radius) + (target_y - 2.0 * radius)
+    };
+
+    if distance < 0.0 {
+        // Already past the target.
+        return None;
+    }
+
+    let time = distance / velocity.y.abs();
+    let x = reflect(position.x + velocity.x * time, radius, width - radius);
+
+    Some((x, time))
+}
+
+/// Paddle position that sends a ball landing at `landing_x` toward `brick`.
+fn aim(
+    landing_x: f32,
+    vertical_speed: f32,
+    paddle_position: Vector2<f32>,
+    paddle_size: Vector2<f32>,
+    brick: Vector2<f32>,
+    ball_speed: f32,
+) -> f32 {
+    let half_width = paddle_size.x / 2.0;
+    let time = (paddle_position.y - brick.y).max(1.0) / vertical_speed.max(1.0);
+
+    // The paddle gives the ball a horizontal speed proportional to where it hits it.
+    let wanted_speed = ((brick.x - landing_x) / time).clamp(-ball_speed, ball_speed);
+    let offset =
+        (wanted_speed / ball_speed * half_width).clamp(-0.8 * half_width, 0.8 * half_width);
+
+    landing_x - offset
+}
+
+pub fn ai_movement(
+    mut ai_query: Query<(Entity, &mut Position, &Size, &mut Player, &mut AiController)>,
+    mut ball_query: Query<(&Position, &mut Velocity, &mut Ball), Without<Player>>,
+    brick_query: Query<(&Position, &BrickKind), Without<Player>>,
+    screeninfo: Res<ScreenInfo>,
+    tuning: Res<Tuning>,
+) {
+    let (ball_speed, player_speed) = (tuning.ball_speed, tuning.player_speed);
+
+    for (entity, mut position, size, mut player, mut ai) in &mut ai_query {
+        let stunned = player.stunned > 0;
+        player.stunned = player.stunned.saturating_sub(1);
+
+        if ai.timer > 0 {
+            ai.timer -= 1;
+        } else {
+            // Look at the field.
+            ai.timer = ai.difficulty.reaction_delay();
+
+            let paddle_top = position.0.y - size.0.y / 2.0;
+            let error = (fastrand::f32() * 2.0 - 1.0) * ai.difficulty.error();
+
+            // Lowest brick, the easiest to reach.
+            let brick = brick_query
+                .iter()
+                .filter(|(_, kind)| kind.is_breakable())
+                .map(|(position, _)| position)
+                .max_by(|a, b| a.0.y.total_cmp(&b.0.y));
+
+            let held = ball_query
+                .iter()
+                .any(|(_, _, ball)| ball.owner == entity && !ball.active);
+
+            // Ball reaching the paddle first.
+            let landing = ball_query
+                .iter()
+                .filter(|(_, _, ball)| ball.active)
+                .filter_map(|(ball_position, velocity, ball)| {
+                    predict_landing(
+                        ball_position.0,
+                        velocity.0,
+                        ball.radius as f32,
+                        paddle_top,
+                        screeninfo.width,
+                    )
+                    .map(|(x, time)| (x, time, velocity.0.y.abs()))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            ai.target_x = match (landing, brick) {
+                (Some((x, _, speed)), Some(brick)) if ai.difficulty.aims() => Some(aim(
+                    x + error,
+                    speed,
+                    position.0,
+                    size.0,
+                    brick.0,
+                    ball_speed,
+                )),
+                (Some((x, ..)), _) => Some(x + error),
+                // The ball is launched straight up, go under a brick.
+                (None, Some(brick)) if held => Some(brick.0.x + error),
+                (None, _) => None,
+            }
+            // Where the paddle can go, or it would never get under a target near a wall.
+            .map(|x| x.clamp(size.0.x / 2.0, screeninfo.width - size.0.x / 2.0));
+
+            // Launch once under the target.
+            if held
+                && !ai
+                    .target_x
+                    .is_some_and(|x| (x - position.0.x).abs() > player_speed)
+            {
+                for (_, mut velocity, mut ball) in &mut ball_query {
+                    if ball.owner == entity && !ball.active {
+                        ball.active = true;
+                        velocity.0 = Vector2::new(0.0, -ball_speed);
+                    }
+                }
+            }
+        }
+
+        if let Some(target_x) = ai.target_x.filter(|_| !stunned) {
+            let step = (target_x - position.0.x).clamp(-player_speed, player_speed);
+            position.0.x += step;
+        }
+
+        position.0.x = position
+            .0
+            .x
+            .clamp(size.0.x / 2.0, screeninfo.width - size.0.x / 2.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::event::Events;
+
+    use crate::game::{
+        audio::AudioEvent,
+        bench::{build_world, Scenario},
+        events::{GameplayEvent, GameplayEventKind},
+        gameplay_schedule,
+    };
+
+    /// Steps the ball has to stay in play, a minute of game.
+    const TICKS: u32 = 3600;
+
+    #[test]
+    fn keeps_the_ball_in_play() {
+        fastrand::seed(3);
+
+        // A ball thrown at random, with no brick to make it unpredictable.
+        let (mut world, _) = build_world(&Scenario {
+            bricks: 0,
+            balls: 1,
+            ticks: TICKS,
+        });
+        let mut schedule = gameplay_schedule();
+        let mut hits = 0;
+
+        for tick in 0..TICKS {
+            schedule.run(&mut world);
+            world.clear_trackers();
+            world.resource_mut::<Events<AudioEvent>>().clear();
+
+            for event in world.resource_mut::<Events<GameplayEvent>>().drain() {
+                assert_ne!(
+                    event.kind,
+                    GameplayEventKind::BallLost,
+                    "ball lost after {tick} steps"
+                );
+
+                if event.kind == GameplayEventKind::PaddleHit {
+                    hits += 1;
+                }
+            }
+        }
+
+        assert!(hits > 0, "the ball never came down");
+    }
+}
@@ -1,8 +1,45 @@
-use bevy_ecs::event::Event;
+use bevy_ecs::event::{Event, EventReader, EventWriter};
 
+use super::events::{GameplayEvent, GameplayEventKind};
 
-#[derive(Event)]
+#[derive(Event, Clone, Copy)]
 pub enum AudioEvent {
     Destroyed,
     Bounce,
+    /// A hazard is sent to the opponent.
+    Attack,
+}
+
+impl AudioEvent {
+    /// Compact representation sent over the network.
+    pub fn to_byte(self) -> u8 {
+        match self {
+            AudioEvent::Destroyed => 0,
+            AudioEvent::Bounce => 1,
+            AudioEvent::Attack => 2,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(AudioEvent::Destroyed),
+            1 => Some(AudioEvent::Bounce),
+            2 => Some(AudioEvent::Attack),
+            _ => None,
+        }
+    }
+}
+
+/// Play the sounds of the gameplay events, others are sent by the systems themselves.
+pub fn gameplay_sounds(
+    mut event_reader: EventReader<GameplayEvent>,
+    mut audio_writer: EventWriter<AudioEvent>,
+) {
+    for event in event_reader.read() {
+        match event.kind {
+            GameplayEventKind::BrickDestroyed => audio_writer.send(AudioEvent::Destroyed),
+            GameplayEventKind::PaddleHit => audio_writer.send(AudioEvent::Bounce),
+            _ => (),
+        }
+    }
 }
@@ -0,0 +1,305 @@
+//! Headless benchmark of the gameplay systems, started with `--bench`.
+//!
+//! `--bench` alone runs a few scenarios, `--bench <bricks> <balls> <ticks>` a
+//! single one. No window is opened: a solo world with a computer controlled
+//! paddle is built and each system runs in its own schedule, so that it can be
+//! timed. Lost balls are put back after each tick to keep the load steady.
+//!
+//! Unlike in the game, the commands of a system are applied as soon as it
+//! returns, and the versus and co-op systems are left out. The same run is then
+//! made with the schedule of the game, whose time is given last.
+
+use std::time::{Duration, Instant};
+
+use bevy_ecs::{
+    entity::Entity,
+    event::Events,
+    query::With,
+    schedule::{IntoSystemConfigs, Schedule},
+    world::World,
+};
+use nalgebra::Vector2;
+use raylib::ffi::Color;
+
+use super::{
+    ai::{ai_movement, AiController, AiDifficulty},
+    audio::{gameplay_sounds, AudioEvent},
+    collisions::{
+        brick_grid_update, collisions_ball_borders, collisions_ball_bricks, collisions_ball_player,
+    },
+    components::{Colored, Position, Size, Velocity},
+    entities::{
+        ball::{
+            ball_reset_event, ball_respawning, update_ball_velocity, Ball, BallBundle,
+            BallResetEvent,
+        },
+        brick::{brick_explosions, brick_sliding, Brick, BrickBundle, BrickKind},
+        hazard::{collisions_hazard_player, hazard_movement},
+        player::{ball_retaining_logic, player_death, player_movement_logic, Player, PlayerBundle},
+    },
+    events::GameplayEvent,
+    gameplay_schedule,
+    resources::{BrickGrid, InputManager, ScreenInfo},
+    rules::{brick_scoring, ScoreEvent, Tuning},
+    BRICKS_PER_LINE, BRICK_HEIGHT,
+};
+
+const SCREEN_WIDTH: f32 = 1366.0;
+const SCREEN_HEIGHT: f32 = 768.0;
+
+/// Seed of the random numbers, so that runs can be compared.
+const SEED: u64 = 42;
+
+const DEFAULT_TICKS: u32 = 1000;
+
+pub struct Scenario {
+    pub bricks: usize,
+    pub balls: usize,
+    pub ticks: u32,
+}
+
+const DEFAULT_SCENARIOS: [Scenario; 3] = [
+    Scenario {
+        bricks: 100,
+        balls: 1,
+        ticks: DEFAULT_TICKS,
+    },
+    Scenario {
+        bricks: 400,
+        balls: 10,
+        ticks: DEFAULT_TICKS,
+    },
+    Scenario {
+        bricks: 2000,
+        balls: 100,
+        ticks: DEFAULT_TICKS,
+    },
+];
+
+/// Take `--bench [<bricks> <balls> <ticks>]` out of `args`.
+pub fn scenarios_from_args(args: &mut Vec<String>) -> Option<Vec<Scenario>> {
+    let index = args.iter().position(|arg| arg == "--bench")?;
+    args.remove(index);
+
+    let mut numbers = vec![];
+    let mut invalid = false;
+
+    while numbers.len() < 3 && args.get(index).is_some_and(|arg| !arg.starts_with("--")) {
+        match args.remove(index).parse() {
+            Ok(number) => numbers.push(number),
+            Err(_) => invalid = true,
+        }
+    }
+
+    let ticks = numbers.get(2).map(|&ticks| u32::try_from(ticks));
+
+    match (&numbers[..], ticks) {
+        ([], _) if !invalid => Some(DEFAULT_SCENARIOS.into()),
+        (&[bricks, balls, _], Some(Ok(ticks))) if !invalid => Some(vec![Scenario {
+            bricks,
+            balls,
+            ticks,
+        }]),
+        _ => {
+            eprintln!("--bench expects a number of bricks, balls and ticks, using the defaults");
+            Some(DEFAULT_SCENARIOS.into())
+        }
+    }
+}
+
+/// Schedule running a single system, to time it.
+fn timed<M>(name: &'static str, system: impl IntoSystemConfigs<M>) -> (&'static str, Schedule) {
+    let mut schedule = Schedule::default();
+    schedule.add_systems(system);
+    (name, schedule)
+}
+
+macro_rules! timed_systems {
+    ($($system:ident),* $(,)?) => {
+        vec![$(timed(stringify!($system), $system)),*]
+    };
+}
+
+fn spawn_ball(world: &mut World, owner: Entity) {
+    let position = Position(Vector2::new(
+        fastrand::f32() * SCREEN_WIDTH,
+        SCREEN_HEIGHT * (0.5 + fastrand::f32() / 4.0),
+    ));
+
+    let speed = Tuning::default().ball_speed;
+    let mut ball = BallBundle::new(owner, Color::MAROON, &position);
+
+    ball.position = position;
+    ball.ball.active = true;
+    ball.velocity = Velocity(Vector2::new((fastrand::f32() - 0.5) * speed, -speed));
+
+    world.spawn(ball);
+}
+
+pub(super) fn build_world(scenario: &Scenario) -> (World, Entity) {
+    let mut world = World::new();
+
+    let screeninfo = ScreenInfo {
+        width: SCREEN_WIDTH,
+        height: SCREEN_HEIGHT,
+    };
+
+    // Bricks fill the upper half of the screen.
+    let rows = scenario.bricks.div_ceil(BRICKS_PER_LINE).max(1);
+    let brick_size = Vector2::new(
+        SCREEN_WIDTH / BRICKS_PER_LINE as f32,
+        (SCREEN_HEIGHT / 2.0 / rows as f32).min(BRICK_HEIGHT),
+    );
+
+    world.insert_resource(screeninfo);
+    world.insert_resource(InputManager::default());
+    world.insert_resource(Tuning::default());
+    world.insert_resource(BrickGrid::new(brick_size));
+    world.insert_resource(Events::<AudioEvent>::default());
+    world.insert_resource(Events::<BallResetEvent>::default());
+    world.insert_resource(Events::<GameplayEvent>::default());
+    world.insert_resource(Events::<ScoreEvent>::default());
+
+    let mut player = PlayerBundle::new(&screeninfo, 0, Color::BLACK, Color::MAROON);
+    // The paddle must survive the whole run.
+    player.player.life = i32::MAX;
+
+    let owner = world
+        .spawn((player, AiController::new(AiDifficulty::Hard)))
+        .id();
+
+    world.spawn_batch((0..scenario.bricks).map(|i| BrickBundle {
+        position: Position(Vector2::new(
+            (i % BRICKS_PER_LINE) as f32 * brick_size.x + brick_size.x / 2.0,
+            (i / BRICKS_PER_LINE) as f32 * brick_size.y + brick_size.y / 2.0,
+        )),
+        size: Size(brick_size),
+        color: Colored(Color::GRAY),
+        kind: BrickKind::Normal,
+        brick: Brick,
+    }));
+
+    for _ in 0..scenario.balls {
+        spawn_ball(&mut world, owner);
+    }
+
+    (world, owner)
+}
+
+/// Run `step` for each tick of the scenario, telling it whether the tick is
+/// counted: the first one initializes the schedules. Lost balls are put back
+/// after each tick.
+fn run_ticks(
+    world: &mut World,
+    owner: Entity,
+    scenario: &Scenario,
+    mut step: impl FnMut(&mut World, bool),
+) {
+    for tick in 0..=scenario.ticks {
+        step(world, tick > 0);
+
+        world.clear_trackers();
+        world.resource_mut::<Events<AudioEvent>>().clear();
+        world.resource_mut::<Events<GameplayEvent>>().clear();
+        world.resource_mut::<Events<ScoreEvent>>().clear();
+        world.resource_mut::<Events<BallResetEvent>>().update();
+
+        let balls = world.query_filtered::<(), With<Ball>>().iter(world).count();
+
+        for _ in balls..scenario.balls {
+            spawn_ball(world, owner);
+        }
+    }
+}
+
+pub fn run(scenario: &Scenario) {
+    fastrand::seed(SEED);
+
+    let (mut world, owner) = build_world(scenario);
+
+    // The systems of `gameplay_schedule` running in solo, in an order it allows.
+    let mut systems = timed_systems![
+        update_ball_velocity,
+        ball_retaining_logic,
+        collisions_ball_borders,
+        brick_grid_update,
+        collisions_ball_bricks,
+        player_death,
+        collisions_ball_player,
+        player_movement_logic,
+        ball_respawning,
+        ball_reset_event,
+        hazard_movement,
+        collisions_hazard_player,
+        ai_movement,
+        brick_explosions,
+        brick_sliding,
+        brick_scoring,
+        gameplay_sounds,
+    ];
+
+    let mut timings = vec![Duration::ZERO; systems.len()];
+
+    run_ticks(&mut world, owner, scenario, |world, counted| {
+        for ((_, schedule), timing) in systems.iter_mut().zip(&mut timings) {
+            let start = Instant::now();
+            schedule.run(world);
+
+            if counted {
+                *timing += start.elapsed();
+            }
+        }
+    });
+
+    // Same run with the schedule of the game.
+    fastrand::seed(SEED);
+
+    let (mut world, owner) = build_world(scenario);
+    let mut schedule = gameplay_schedule();
+    let mut game_timing = Duration::ZERO;
+
+    run_ticks(&mut world, owner, scenario, |world, counted| {
+        let start = Instant::now();
+        schedule.run(world);
+
+        if counted {
+            game_timing += start.elapsed();
+        }
+    });
+
+    let total: Duration = timings.iter().sum();
+    let per_tick = |duration: Duration| duration.as_secs_f64() * 1e6 / scenario.ticks.max(1) as f64;
+
+    println!(
+        "{} bricks, {} balls, {} ticks",
+        scenario.bricks, scenario.balls, scenario.ticks
+    );
+
+    let mut rows: Vec<(&str, Duration)> =
+        systems.iter().map(|(name, _)| *name).zip(timings).collect();
+    rows.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+
+    for (name, duration) in rows {
+        println!(
+            "  {name:<24} {:>10.2} µs/tick {:>6.1} %",
+            per_tick(duration),
+            duration.as_secs_f64() * 100.0 / total.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+
+    let bricks_left = world
+        .query_filtered::<(), With<Brick>>()
+        .iter(&world)
+        .count();
+
+    println!("  {:<24} {:>10.2} µs/tick", "total", per_tick(total));
+    println!(
+        "  {:<24} {:>10.2} µs/tick",
+        "game schedule",
+        per_tick(game_timing)
+    );
+    println!(
+        "  {bricks_left} bricks left, score {}",
+        world.get::<Player>(owner).map_or(0, |player| player.score)
+    );
+}
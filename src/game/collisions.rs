@@ -1,35 +1,68 @@
 use bevy_ecs::{
     entity::Entity,
     event::EventWriter,
-    query::{With, Without},
-    system::{Commands, Query, Res},
+    query::{Added, Has, With, Without},
+    removal_detection::RemovedComponents,
+    system::{Commands, Query, Res, ResMut},
 };
+use nalgebra::Vector2;
 use raylib::ffi::Rectangle;
 
 use super::{
     audio::AudioEvent,
     components::{Position, Size, Velocity},
-    entities::{ball::Ball, brick::Brick, player::Player},
-    resources::ScreenInfo,
+    entities::{
+        ball::Ball,
+        brick::{ignite_neighbours, Brick, BrickKind, Detonating, Sliding},
+        player::{Downed, Player},
+    },
+    events::{Cause, GameplayEvent, GameplayEventKind},
+    resources::{BrickGrid, ScreenInfo},
+    rules::Tuning,
 };
 
-const BALL_SPEED: f32 = 10.0f32;
+pub const BALL_SPEED: f32 = 10.0f32;
+
+/// Bricks a ball may hit, exploding ones being already gone.
+type BrickQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Size,
+        &'static BrickKind,
+        Option<&'static Velocity>,
+    ),
+    (With<Brick>, Without<Player>, Without<Detonating>),
+>;
+
+/// Balls, which are neither bricks nor paddles.
+type BallQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Position, &'static Ball, &'static mut Velocity),
+    (Without<Brick>, Without<Player>),
+>;
 
 pub fn collisions_ball_borders(
     mut ball_query: Query<(Entity, &Position, &Ball, &mut Velocity)>,
+    player_query: Query<&Player>,
     mut audio_writer: EventWriter<AudioEvent>,
+    mut event_writer: EventWriter<GameplayEvent>,
     screeninfo: Res<ScreenInfo>,
     mut commands: Commands,
 ) {
     for (entity, position, ball, mut velocity) in &mut ball_query {
-        if position.0.x + ball.radius as f32 >= screeninfo.width
-            || position.0.x - ball.radius as f32 <= 0.0
+        // Only balls moving into a wall bounce, those pushed into it would stick there.
+        if (position.0.x + ball.radius as f32 >= screeninfo.width && velocity.0.x > 0.0)
+            || (position.0.x - ball.radius as f32 <= 0.0 && velocity.0.x < 0.0)
         {
             velocity.0.x *= -1.0;
             audio_writer.send(AudioEvent::Bounce);
         }
 
-        if position.0.y - ball.radius as f32 <= 0.0 {
+        if position.0.y - ball.radius as f32 <= 0.0 && velocity.0.y < 0.0 {
             velocity.0.y *= -1.0;
             audio_writer.send(AudioEvent::Bounce);
         }
@@ -37,17 +70,29 @@ pub fn collisions_ball_borders(
         if position.0.y + ball.radius as f32 >= screeninfo.height {
             // Out of map
             commands.entity(entity).despawn();
+
+            let mut event =
+                GameplayEvent::new(GameplayEventKind::BallLost, Cause::Fall, position.0)
+                    .entity(entity);
+
+            // The player may be dead already.
+            if let Ok(player) = player_query.get(ball.owner) {
+                event = event.player(player.index);
+            }
+
+            event_writer.send(event);
         }
     }
 }
 
 pub fn collisions_ball_player(
     mut ball_query: Query<(&Position, &Ball, &mut Velocity), Without<Player>>,
-    player_query: Query<(&Position, &Size), With<Player>>,
-    mut audio_writer: EventWriter<AudioEvent>,
+    player_query: Query<(Entity, &Position, &Size, &Player), Without<Downed>>,
+    mut event_writer: EventWriter<GameplayEvent>,
+    tuning: Res<Tuning>,
 ) {
     for (position, ball, mut velocity) in &mut ball_query {
-        for (player_position, player_size) in &player_query {
+        for (entity, player_position, player_size, player) in &player_query {
             let r = Rectangle::new(
                 player_position.0.x - player_size.0.x / 2.0,
                 player_position.0.y - player_size.0.y / 2.0,
@@ -56,102 +101,150 @@ pub fn collisions_ball_player(
             );
 
             if r.check_collision_circle_rec(position.0, ball.radius as f32) && velocity.0.y > 0.0 {
-                velocity.0.y *= -1.0;
-                velocity.0.x = (position.0.x - player_position.0.x) / (player_size.0.x / 2.0) * BALL_SPEED;
-                audio_writer.send(AudioEvent::Bounce);
+                // Speed the ball up, without going over the limit nor slowing it down.
+                let speed = (velocity.0.y * tuning.ball_acceleration)
+                    .min(tuning.max_ball_speed)
+                    .max(velocity.0.y);
+
+                velocity.0.y = -speed;
+                velocity.0.x =
+                    (position.0.x - player_position.0.x) / (player_size.0.x / 2.0) * speed;
+                event_writer.send(
+                    GameplayEvent::new(GameplayEventKind::PaddleHit, Cause::Ball, position.0)
+                        .entity(entity)
+                        .player(player.index),
+                );
                 break;
             }
         }
     }
 }
 
+/// Keep the broad phase in sync with the bricks spawned and destroyed.
+pub fn brick_grid_update(
+    mut grid: ResMut<BrickGrid>,
+    added_query: Query<(Entity, &Position, Has<Sliding>), Added<Brick>>,
+    mut removed: RemovedComponents<Brick>,
+) {
+    for entity in removed.read() {
+        grid.remove(entity);
+    }
+
+    // Moving bricks are always checked.
+    for (entity, position, _) in added_query.iter().filter(|(_, _, moving)| !moving) {
+        grid.insert(entity, position.0);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn collisions_ball_bricks(
-    mut player_query: Query<&mut Player, (Without<Brick>, With<Player>)>,
-    brick_query: Query<(Entity, &Position, &Size), (With<Brick>, Without<Player>)>,
-    mut ball_query: Query<(&Position, &Ball, &mut Velocity), (Without<Brick>, Without<Player>)>,
+    player_query: Query<&Player, Without<Brick>>,
+    brick_query: BrickQuery,
+    moving_query: Query<Entity, With<Sliding>>,
+    mut ball_query: BallQuery,
+    grid: Res<BrickGrid>,
     mut audio_writer: EventWriter<AudioEvent>,
+    mut event_writer: EventWriter<GameplayEvent>,
     mut commands: Commands,
 ) {
     for (position, ball, mut velocity) in &mut ball_query {
-        for (brick_entity, brick_position, brick_size) in &brick_query {
-            // Hit below
-            if (position.0.y - ball.radius as f32 <= brick_position.0.y + brick_size.0.y / 2.0)
+        let Ok(player) = player_query.get(ball.owner) else {
+            // The owner was eliminated, its balls are despawned with it.
+            continue;
+        };
+
+        // Only look at the bricks around the ball, and at the moving ones.
+        let reach = Vector2::repeat(ball.radius as f32) + velocity.0.abs();
+        let candidates = grid
+            .query(position.0 - reach, position.0 + reach)
+            .chain(moving_query.iter());
+
+        for brick_entity in candidates {
+            let Ok((_, brick_position, brick_size, kind, brick_velocity)) =
+                brick_query.get(brick_entity)
+            else {
+                // Already exploding, or destroyed since the grid was updated.
+                continue;
+            };
+
+            // Moving bricks are hit as seen from the brick.
+            let brick_velocity = brick_velocity.map_or(Vector2::zeros(), |velocity| velocity.0);
+            let relative = velocity.0 - brick_velocity;
+
+            let hit_below = (position.0.y - ball.radius as f32
+                <= brick_position.0.y + brick_size.0.y / 2.0)
                 && (position.0.y - ball.radius as f32
-                    > brick_position.0.y + brick_size.0.y / 2.0 + velocity.0.y)
+                    > brick_position.0.y + brick_size.0.y / 2.0 + relative.y)
                 && ((position.0.x - brick_position.0.x).abs()
                     < brick_size.0.x / 2.0 + ball.radius as f32 * 2.0 / 3.0)
-                && velocity.0.y < 0.0
-            {
-                velocity.0.y *= -1.0;
-                audio_writer.send(AudioEvent::Destroyed);
+                && relative.y < 0.0;
 
-                player_query
-                    .get_mut(ball.owner)
-                    .expect("Ball without player ?")
-                    .score += 1;
-
-                commands.entity(brick_entity).despawn();
-            }
-            // Hit above
-            else if position.0.y + ball.radius as f32 >= brick_position.0.y - brick_size.0.y / 2.0
+            let hit_above = position.0.y + ball.radius as f32
+                >= brick_position.0.y - brick_size.0.y / 2.0
                 && (position.0.y + ball.radius as f32)
-                    .partial_cmp(&(brick_position.0.y - brick_size.0.y / 2.0 + velocity.0.y))
+                    .partial_cmp(&(brick_position.0.y - brick_size.0.y / 2.0 + relative.y))
                     .unwrap()
                     == std::cmp::Ordering::Less
                 && (position.0.x - brick_position.0.x).abs()
                     < brick_size.0.x / 2.0 + ball.radius as f32 * 2.0 / 3.0
-                && velocity.0.y > 0.0
-            {
-                velocity.0.y *= -1.0;
-
-                audio_writer.send(AudioEvent::Destroyed);
+                && relative.y > 0.0;
 
-                player_query
-                    .get_mut(ball.owner)
-                    .expect("Ball without player ?")
-                    .score += 1;
-
-                commands.entity(brick_entity).despawn();
-            }
-            // Hit Left
-            else if ((position.0.x + ball.radius as f32)
+            let hit_left = ((position.0.x + ball.radius as f32)
                 >= (brick_position.0.x - brick_size.0.x / 2.0))
                 && ((position.0.x + ball.radius as f32)
-                    < (brick_position.0.x - brick_size.0.x / 2.0 + velocity.0.x))
+                    < (brick_position.0.x - brick_size.0.x / 2.0 + relative.x))
                 && (((position.0.y - brick_position.0.y).abs())
                     < (brick_size.0.y / 2.0 + ball.radius as f32 * 2.0 / 3.0))
-                && (velocity.0.x > 0.0)
-            {
-                velocity.0.x *= -1.0;
-
-                audio_writer.send(AudioEvent::Destroyed);
+                && (relative.x > 0.0);
 
-                player_query
-                    .get_mut(ball.owner)
-                    .expect("Ball without player ?")
-                    .score += 1;
-
-                commands.entity(brick_entity).despawn();
-            }
-            // Hit right
-            else if ((position.0.x - ball.radius as f32)
+            let hit_right = ((position.0.x - ball.radius as f32)
                 <= (brick_position.0.x + brick_size.0.x / 2.0))
                 && ((position.0.x - ball.radius as f32)
-                    > (brick_position.0.x + brick_size.0.x / 2.0 + velocity.0.x))
+                    > (brick_position.0.x + brick_size.0.x / 2.0 + relative.x))
                 && (((position.0.y - brick_position.0.y).abs())
                     < (brick_size.0.y / 2.0 + ball.radius as f32 * 2.0 / 3.0))
-                && (velocity.0.x < 0.0)
-            {
-                velocity.0.x *= -1.0;
+                && (relative.x < 0.0);
+
+            let hit = if hit_below || hit_above {
+                velocity.0.y = brick_velocity.y - relative.y;
+                true
+            } else if hit_left || hit_right {
+                velocity.0.x = brick_velocity.x - relative.x;
+                true
+            } else {
+                false
+            };
+
+            if !hit {
+                continue;
+            }
 
-                audio_writer.send(AudioEvent::Destroyed);
+            if !kind.is_breakable() {
+                audio_writer.send(AudioEvent::Bounce);
+                continue;
+            }
 
-                player_query
-                    .get_mut(ball.owner)
-                    .expect("Ball without player ?")
-                    .score += 1;
+            commands.entity(brick_entity).despawn();
+            event_writer.send(
+                GameplayEvent::new(
+                    GameplayEventKind::BrickDestroyed,
+                    Cause::Ball,
+                    brick_position.0,
+                )
+                .entity(brick_entity)
+                .player(player.index),
+            );
 
-                commands.entity(brick_entity).despawn();
+            if *kind == BrickKind::Explosive {
+                ignite_neighbours(
+                    &mut commands,
+                    brick_query
+                        .iter()
+                        .map(|(entity, position, _, kind, _)| (entity, position, kind)),
+                    brick_position.0,
+                    brick_size.0,
+                    Some(ball.owner),
+                );
             }
         }
     }
@@ -1,19 +1,28 @@
 use bevy_ecs::{
     bundle::Bundle,
+    change_detection::DetectChanges,
     component::Component,
     entity::Entity,
-    event::{Event, EventReader},
+    event::{Event, EventReader, EventWriter},
     query::{With, Without},
-    system::{Commands, Query},
+    system::{Commands, Query, ResMut},
 };
 use nalgebra::Vector2;
 use raylib::{
     core::drawing::{RaylibDraw, RaylibDrawHandle},
-    ffi::Color,
+    ffi::{Color, Rectangle},
 };
 
-use super::player::Player;
-use crate::game::components::{Colored, Position, Velocity};
+use super::player::{Downed, Player};
+use crate::{
+    game::{
+        components::{Colored, Position, Velocity},
+        events::{Cause, GameplayEvent, GameplayEventKind},
+        palette::Palette,
+        rules::CoopState,
+    },
+    theme::{draw_sprite, Theme},
+};
 
 #[derive(Component)]
 pub struct Ball {
@@ -85,23 +94,61 @@ pub fn ball_reset_event(
     }
 }
 
-pub fn draw_ball(d: &RaylibDrawHandle, (position, ball, color): (&Position, &Ball, &Colored)) {
-    // Draw ball
-    d.draw_circle_v(position.0, ball.radius as f32, color.0);
+pub fn draw_ball(
+    d: &RaylibDrawHandle,
+    theme: &Theme,
+    palette: Palette,
+    (position, ball, color): (&Position, &Ball, &Colored),
+) {
+    let radius = ball.radius as f32;
+    let color = palette.resolve(color.0);
+
+    match theme.ball() {
+        Some(sprite) => {
+            let dest = Rectangle::new(
+                position.0.x - radius,
+                position.0.y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            );
+            draw_sprite(d, sprite, dest, color);
+        }
+        None => d.draw_circle_v(position.0, radius, color),
+    }
 }
 
 pub fn ball_respawning(
     ball_query: Query<&Ball, Without<Player>>,
-    mut player_query: Query<(Entity, &Position, &mut Player), With<Player>>,
+    mut player_query: Query<(Entity, &Position, &mut Player), Without<Downed>>,
+    mut coop: Option<ResMut<CoopState>>,
+    mut event_writer: EventWriter<GameplayEvent>,
     mut commands: Commands,
 ) {
     for (player_entity, position, mut player) in &mut player_query {
         if ball_query.iter().all(|ball| ball.owner != player_entity) {
+            if let Some(coop) = coop.as_mut() {
+                // In cooperative mode, losing the ball downs the player until
+                // the team spends a shared life to bring it back.
+                if std::mem::replace(&mut coop.started[player.index], true) {
+                    commands.entity(player_entity).insert(Downed);
+                    continue;
+                }
+            } else {
+                // Decrease life count.
+                player.life -= 1;
+
+                // The first ball of a paddle is served, not lost.
+                if !player.is_added() {
+                    event_writer.send(
+                        GameplayEvent::new(GameplayEventKind::LifeLost, Cause::Fall, position.0)
+                            .entity(player_entity)
+                            .player(player.index),
+                    );
+                }
+            }
+
             // Create a new ball for this player.
             commands.spawn(BallBundle::new(player_entity, player.ball_color, position));
-
-            // Decrease life count.
-            player.life -= 1;
         }
     }
 }
@@ -1,25 +1,264 @@
-use bevy_ecs::{bundle::Bundle, component::Component};
-use raylib::core::drawing::{RaylibDraw, RaylibDrawHandle};
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::EventWriter,
+    query::{With, Without},
+    system::{Commands, Query},
+};
+use nalgebra::Vector2;
+use raylib::{
+    core::drawing::{RaylibDraw, RaylibDrawHandle},
+    ffi::{Color, Rectangle},
+};
 
-use crate::game::components::{Colored, Position, Size};
+use super::player::Player;
+use crate::{
+    game::{
+        components::{Colored, Position, Size, Velocity},
+        events::{Cause, GameplayEvent, GameplayEventKind},
+        palette::Palette,
+    },
+    theme::{draw_sprite, Theme},
+};
+
+/// Frames between the explosion of a brick and the one of its neighbours.
+const EXPLOSION_DELAY: u32 = 8;
+
+/// Horizontal speed of moving bricks.
+pub const SLIDING_SPEED: f32 = 2.0;
 
 #[derive(Component)]
 pub struct Brick;
 
+/// Behaviour of a brick.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BrickKind {
+    #[default]
+    Normal,
+    /// Destroys its neighbours when destroyed, which may explode in turn.
+    Explosive,
+    /// Never breaks.
+    Steel,
+    /// Slides along its row.
+    Moving,
+}
+
+impl BrickKind {
+    pub const ALL: [BrickKind; 4] = [
+        BrickKind::Normal,
+        BrickKind::Explosive,
+        BrickKind::Steel,
+        BrickKind::Moving,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BrickKind::Normal => "Normale",
+            BrickKind::Explosive => "Explosive",
+            BrickKind::Steel => "Acier",
+            BrickKind::Moving => "Mobile",
+        }
+    }
+
+    /// Whether the brick has to be destroyed to clear the stage.
+    pub fn is_breakable(self) -> bool {
+        self != BrickKind::Steel
+    }
+
+    /// Compact representation sent over the network.
+    pub fn to_byte(self) -> u8 {
+        match self {
+            BrickKind::Normal => 0,
+            BrickKind::Explosive => 1,
+            BrickKind::Steel => 2,
+            BrickKind::Moving => 3,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        BrickKind::ALL.get(byte as usize).copied()
+    }
+}
+
+/// Moving brick, sliding back and forth between `min` and `max` on the x axis.
+#[derive(Component)]
+pub struct Sliding {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Brick caught in an explosion, destroyed when `timer` reaches 0.
+#[derive(Component)]
+pub struct Detonating {
+    pub timer: u32,
+
+    /// Player who triggered the chain reaction, scoring the brick. `None`
+    /// when they were gone by the time the game was saved.
+    pub player: Option<Entity>,
+}
+
 #[derive(Bundle)]
 pub struct BrickBundle {
     pub position: Position,
     pub size: Size,
     pub color: Colored,
+    pub kind: BrickKind,
     pub brick: Brick,
 }
 
-pub fn draw_brick(d: &RaylibDrawHandle, (position, size, color): (&Position, &Size, &Colored)) {
-    d.draw_rectangle(
-        (position.0.x - size.0.x / 2.0) as i32,
-        (position.0.y - size.0.y / 2.0) as i32,
-        size.0.x as i32,
-        size.0.y as i32,
-        color.0,
-    );
+/// Set the breakable bricks around an exploded one to explode shortly after.
+pub fn ignite_neighbours<'a>(
+    commands: &mut Commands,
+    bricks: impl Iterator<Item = (Entity, &'a Position, &'a BrickKind)>,
+    origin: Vector2<f32>,
+    size: Vector2<f32>,
+    player: Option<Entity>,
+) {
+    for (entity, position, kind) in bricks {
+        let offset = position.0 - origin;
+
+        if kind.is_breakable()
+            && offset != Vector2::zeros()
+            && offset.x.abs() < size.x * 1.5
+            && offset.y.abs() < size.y * 1.5
+        {
+            commands.entity(entity).try_insert(Detonating {
+                timer: EXPLOSION_DELAY,
+                player,
+            });
+        }
+    }
+}
+
+pub fn brick_explosions(
+    mut detonating_query: Query<(Entity, &Position, &Size, &BrickKind, &mut Detonating)>,
+    brick_query: Query<(Entity, &Position, &BrickKind), Without<Detonating>>,
+    player_query: Query<&Player>,
+    mut event_writer: EventWriter<GameplayEvent>,
+    mut commands: Commands,
+) {
+    for (entity, position, size, kind, mut detonating) in &mut detonating_query {
+        if detonating.timer > 0 {
+            detonating.timer -= 1;
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+
+        let mut event = GameplayEvent::new(
+            GameplayEventKind::BrickDestroyed,
+            Cause::Explosion,
+            position.0,
+        )
+        .entity(entity);
+
+        // The player may be dead already.
+        if let Some(Ok(player)) = detonating.player.map(|player| player_query.get(player)) {
+            event = event.player(player.index);
+        }
+
+        event_writer.send(event);
+
+        if *kind == BrickKind::Explosive {
+            ignite_neighbours(
+                &mut commands,
+                brick_query.iter(),
+                position.0,
+                size.0,
+                detonating.player,
+            );
+        }
+    }
+}
+
+pub fn brick_sliding(mut query: Query<(&mut Position, &mut Velocity, &Sliding), With<Brick>>) {
+    for (mut position, mut velocity, sliding) in &mut query {
+        position.0 += velocity.0;
+
+        if position.0.x <= sliding.min || position.0.x >= sliding.max {
+            position.0.x = position.0.x.clamp(sliding.min, sliding.max);
+            velocity.0.x *= -1.0;
+        }
+    }
+}
+
+pub fn draw_brick(
+    d: &RaylibDrawHandle,
+    theme: &Theme,
+    palette: Palette,
+    (position, size, color, kind, detonating): (
+        &Position,
+        &Size,
+        &Colored,
+        &BrickKind,
+        Option<&Detonating>,
+    ),
+) {
+    // Blink before exploding.
+    let color = match detonating {
+        Some(detonating) if detonating.timer % 4 < 2 => Color::WHITE,
+        _ => palette.resolve(color.0),
+    };
+
+    let (x, y) = (position.0.x - size.0.x / 2.0, position.0.y - size.0.y / 2.0);
+
+    if let Some(sprite) = theme.brick(*kind) {
+        draw_sprite(d, sprite, Rectangle::new(x, y, size.0.x, size.0.y), color);
+        return;
+    }
+
+    let (x, y, w, h) = (x as i32, y as i32, size.0.x as i32, size.0.y as i32);
+
+    d.draw_rectangle(x, y, w, h, color);
+    draw_brick_kind(d, *kind, x, y, w, h);
+
+    if palette.outlines() {
+        d.draw_rectangle_lines(x, y, w, h, Color::BLACK);
+    }
+}
+
+/// Mark telling the kind of a brick drawn at this place.
+pub fn draw_brick_kind(d: &RaylibDrawHandle, kind: BrickKind, x: i32, y: i32, w: i32, h: i32) {
+    let (center_x, center_y) = (x + w / 2, y + h / 2);
+
+    match kind {
+        BrickKind::Normal => (),
+        BrickKind::Explosive => d.draw_circle(center_x, center_y, h as f32 / 4.0, Color::MAROON),
+        BrickKind::Steel => {
+            d.draw_rectangle_lines(x, y, w, h, Color::DARKGRAY);
+            d.draw_rectangle_lines(x + 4, y + 4, w - 8, h - 8, Color::DARKGRAY);
+        }
+        BrickKind::Moving => {
+            d.draw_line(x + w / 4, center_y, x + w * 3 / 4, center_y, Color::BLACK);
+            d.draw_line(
+                x + w / 4,
+                center_y,
+                x + w / 4 + 6,
+                center_y - 6,
+                Color::BLACK,
+            );
+            d.draw_line(
+                x + w / 4,
+                center_y,
+                x + w / 4 + 6,
+                center_y + 6,
+                Color::BLACK,
+            );
+            d.draw_line(
+                x + w * 3 / 4,
+                center_y,
+                x + w * 3 / 4 - 6,
+                center_y - 6,
+                Color::BLACK,
+            );
+            d.draw_line(
+                x + w * 3 / 4,
+                center_y,
+                x + w * 3 / 4 - 6,
+                center_y + 6,
+                Color::BLACK,
+            );
+        }
+    }
 }
@@ -0,0 +1,110 @@
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::EventWriter,
+    query::{With, Without},
+    system::{Commands, Query, Res},
+};
+use nalgebra::Vector2;
+use raylib::{
+    core::drawing::{RaylibDraw, RaylibDrawHandle},
+    ffi::{Color, Rectangle},
+};
+
+use super::player::Player;
+use crate::game::{
+    audio::AudioEvent,
+    components::{Colored, Position, Size, Velocity},
+    palette::Palette,
+    resources::ScreenInfo,
+};
+
+const HAZARD_SPEED: f32 = 6.0;
+const HAZARD_RADIUS: f32 = 10.0;
+
+/// Number of frames a paddle stays frozen after being hit by a hazard.
+const STUN_DURATION: u32 = 90;
+
+/// Falling hazard sent by a player to one of its opponents.
+#[derive(Component)]
+pub struct Hazard {
+    /// Player paddle targeted by the hazard.
+    pub target: Entity,
+    pub radius: f32,
+}
+
+#[derive(Bundle)]
+pub struct HazardBundle {
+    pub position: Position,
+    pub velocity: Velocity,
+    pub hazard: Hazard,
+    pub color: Colored,
+}
+
+pub fn hazard_movement(mut query: Query<(&mut Position, &Velocity), With<Hazard>>) {
+    for (mut position, velocity) in &mut query {
+        position.0 += velocity.0;
+    }
+}
+
+pub fn collisions_hazard_player(
+    hazard_query: Query<(Entity, &Position, &Hazard)>,
+    mut player_query: Query<(&Position, &Size, &mut Player), Without<Hazard>>,
+    mut audio_writer: EventWriter<AudioEvent>,
+    screeninfo: Res<ScreenInfo>,
+    mut commands: Commands,
+) {
+    for (entity, position, hazard) in &hazard_query {
+        let Ok((player_position, player_size, mut player)) = player_query.get_mut(hazard.target)
+        else {
+            // Target is no longer in game.
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let r = Rectangle::new(
+            player_position.0.x - player_size.0.x / 2.0,
+            player_position.0.y - player_size.0.y / 2.0,
+            player_size.0.x,
+            player_size.0.y,
+        );
+
+        if r.check_collision_circle_rec(position.0, hazard.radius) {
+            player.stunned = STUN_DURATION;
+            audio_writer.send(AudioEvent::Destroyed);
+            commands.entity(entity).despawn();
+        } else if position.0.y - hazard.radius >= screeninfo.height {
+            // Dodged
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn draw_hazard(
+    d: &RaylibDrawHandle,
+    palette: Palette,
+    (position, hazard, color): (&Position, &Hazard, &Colored),
+) {
+    d.draw_circle_v(position.0, hazard.radius, palette.resolve(color.0));
+    d.draw_circle_lines(
+        position.0.x as i32,
+        position.0.y as i32,
+        hazard.radius + 3.0,
+        Color::BLACK,
+    );
+}
+
+impl HazardBundle {
+    pub fn new(target: Entity, position: Vector2<f32>) -> Self {
+        Self {
+            position: Position(position),
+            velocity: Velocity(Vector2::new(0.0, HAZARD_SPEED)),
+            hazard: Hazard {
+                target,
+                radius: HAZARD_RADIUS,
+            },
+            color: Colored(Color::ORANGE),
+        }
+    }
+}
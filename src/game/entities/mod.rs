@@ -1,3 +1,4 @@
 pub mod ball;
 pub mod brick;
-pub mod player;
\ No newline at end of file
+pub mod hazard;
+pub mod player;
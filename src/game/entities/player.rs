@@ -2,27 +2,35 @@ use bevy_ecs::{
     bundle::Bundle,
     component::Component,
     entity::Entity,
+    event::EventWriter,
     query::{With, Without},
-    system::{Commands, Query, Res},
+    system::{Commands, Query, Res, ResMut},
 };
 use nalgebra::Vector2;
 use raylib::{
-    core::{
-        drawing::{RaylibDraw, RaylibDrawHandle},
-        RaylibHandle,
-    },
-    ffi::{Color, KeyboardKey},
+    core::drawing::{RaylibDraw, RaylibDrawHandle},
+    ffi::{Color, KeyboardKey, Rectangle},
 };
 
-use crate::game::{
-    components::{Colored, Position, Size, Velocity},
-    resources::{InputManager, ScreenInfo},
+use crate::{
+    game::{
+        components::{Colored, Position, Size, Velocity},
+        events::{Cause, GameplayEvent, GameplayEventKind},
+        palette::Palette,
+        resources::{InputManager, ScreenInfo},
+        rules::{RoundPhase, Tuning, VersusState},
+    },
+    theme::{draw_sliced, Theme},
 };
 
 use super::ball::Ball;
 
-const PLAYER_MAX_LIFE: i32 = 5;
-const PLAYER_SPEED: f32 = 10.0;
+pub const PLAYER_MAX_LIFE: i32 = 5;
+pub const PLAYER_SPEED: f32 = 10.0;
+
+/// Bricks to break in a row to raise the score multiplier by one.
+const COMBO_STEP: u32 = 4;
+const MAX_MULTIPLIER: u32 = 8;
 
 #[derive(Component)]
 pub struct PlayerControls {
@@ -33,45 +41,66 @@ pub struct PlayerControls {
 
 #[derive(Component)]
 pub struct Player {
+    /// Index of the player (0 for the first player)
+    pub index: usize,
+
     /// Remaining lives
     pub life: i32,
 
-    /// Offset to apply to display UI (scores, lives)
-    pub ui_display_offset: i32,
-
     /// Score
     pub score: u32,
 
     /// Color of the balls associated to the player.
     pub ball_color: Color,
+
+    /// Remaining frames during which the paddle can't move.
+    pub stunned: u32,
+
+    /// Bricks broken in a row since the balls of the player last touched its
+    /// paddle or were lost.
+    pub combo: u32,
 }
 
+impl Player {
+    /// Points the next brick is worth.
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.combo / COMBO_STEP).min(MAX_MULTIPLIER)
+    }
+}
+
+/// Player that lost its ball in cooperative mode and waits to be revived.
+#[derive(Component)]
+pub struct Downed;
+
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub position: Position,
     pub size: Size,
     pub player: Player,
-    pub controls: PlayerControls,
     pub color: Colored,
 }
 
 pub fn player_movement_logic(
-    mut query: Query<(&mut Position, &Size, &PlayerControls)>,
+    mut query: Query<(&mut Position, &Size, &mut Player, &PlayerControls)>,
     screeninfo: Res<ScreenInfo>,
     input: Res<InputManager>,
+    tuning: Res<Tuning>,
 ) {
-    for (mut position, size, controls) in &mut query {
+    for (mut position, size, mut player, controls) in &mut query {
+        let stunned = player.stunned > 0;
+        player.stunned = player.stunned.saturating_sub(1);
+
         // player movement logic
-        if input.is_key_down(controls.left) {
-            position.0.x -= PLAYER_SPEED;
+        if input.is_key_down(controls.left) && !stunned {
+            position.0.x -= tuning.player_speed;
         }
 
         if position.0.x - size.0.x / 2.0 <= 0.0 {
             position.0.x = size.0.x / 2.0;
         }
 
-        if input.is_key_down(controls.right) {
-            position.0.x += PLAYER_SPEED;
+        if input.is_key_down(controls.right) && !stunned {
+            position.0.x += tuning.player_speed;
         }
 
         if position.0.x + size.0.x / 2.0 >= screeninfo.width {
@@ -81,13 +110,32 @@ pub fn player_movement_logic(
 }
 
 pub fn player_death(
-    player_query: Query<(Entity, &Player)>,
+    player_query: Query<(Entity, &Position, &Player)>,
     ball_query: Query<(Entity, &Ball)>,
+    mut versus: Option<ResMut<VersusState>>,
+    mut event_writer: EventWriter<GameplayEvent>,
     mut commands: Commands,
 ) {
-    for (entity, player) in &player_query {
+    for (entity, position, player) in &player_query {
         if player.life <= 0 {
+            // The round scores of the other players are added up when the round ends.
+            if let Some(versus) = versus
+                .as_mut()
+                .filter(|versus| versus.phase == RoundPhase::Playing)
+            {
+                versus.total_scores[player.index] += player.score;
+            }
+
             commands.entity(entity).despawn();
+            event_writer.send(
+                GameplayEvent::new(
+                    GameplayEventKind::PlayerEliminated,
+                    Cause::NoLivesLeft,
+                    position.0,
+                )
+                .entity(entity)
+                .player(player.index),
+            );
 
             // Kill all player balls
             for (ball_entity, _) in ball_query.iter().filter(|(_, ball)| ball.owner == entity) {
@@ -99,9 +147,10 @@ pub fn player_death(
 
 pub fn ball_retaining_logic(
     mut balls_query: Query<(&mut Position, &mut Velocity, &mut Ball), With<Ball>>,
-    players_query: Query<(&Position, &PlayerControls), Without<Ball>>,
+    players_query: Query<(&Position, Option<&PlayerControls>), Without<Ball>>,
     input: Res<InputManager>,
     screeninfo: Res<ScreenInfo>,
+    tuning: Res<Tuning>,
 ) {
     for (mut position, mut velocity, mut ball) in &mut balls_query {
         if !ball.active {
@@ -110,10 +159,10 @@ pub fn ball_retaining_logic(
                 position.0 =
                     Vector2::new(player_position.0.x, screeninfo.height * 7.0 / 8.0 - 30.0);
 
-                // Ball launching logic
-                if input.is_key_pressed(controls.launch) {
+                // Ball launching logic (computer players launch their balls themselves)
+                if controls.is_some_and(|controls| input.is_key_pressed(controls.launch)) {
                     ball.active = true;
-                    velocity.0 = Vector2::new(0.0, -10.0);
+                    velocity.0 = Vector2::new(0.0, -tuning.ball_speed);
                 }
             } else {
                 ball.active = true;
@@ -122,63 +171,99 @@ pub fn ball_retaining_logic(
     }
 }
 
+/// Shape telling a player apart without relying on its colour, drawn on its
+/// paddle and next to its score.
+pub fn draw_player_mark(
+    d: &RaylibDrawHandle,
+    index: usize,
+    x: i32,
+    y: i32,
+    size: i32,
+    color: Color,
+) {
+    match index % 4 {
+        0 => d.draw_circle(x, y, size as f32, color),
+        1 => d.draw_rectangle(x - size, y - size, size * 2, size * 2, color),
+        2 => {
+            d.draw_line(x, y - size, x + size, y, color);
+            d.draw_line(x + size, y, x, y + size, color);
+            d.draw_line(x, y + size, x - size, y, color);
+            d.draw_line(x - size, y, x, y - size, color);
+        }
+        _ => {
+            d.draw_line(x - size, y - size, x + size, y + size, color);
+            d.draw_line(x + size, y - size, x - size, y + size, color);
+        }
+    }
+}
+
 pub fn draw_player(
     d: &RaylibDrawHandle,
-    (position, size, player, color): (&Position, &Size, &Player, &Colored),
-    screeninfo: &ScreenInfo,
+    theme: &Theme,
+    palette: Palette,
+    (position, size, player, color, downed): (&Position, &Size, &Player, &Colored, Option<&Downed>),
 ) {
-    // Draw player bar
-    d.draw_rectangle(
-        (position.0.x - size.0.x / 2.0) as i32,
-        (position.0.y - size.0.y / 2.0) as i32,
-        size.0.x as i32,
-        size.0.y as i32,
-        color.0,
-    );
+    // Draw player bar (grayed out while stunned or downed)
+    let color = palette.resolve(color.0);
+    let bar_color = if player.stunned > 0 || downed.is_some() {
+        Color::GRAY
+    } else {
+        color
+    };
+
+    let (x, y) = (position.0.x - size.0.x / 2.0, position.0.y - size.0.y / 2.0);
+
+    match theme.paddle() {
+        Some((sprite, cap)) => {
+            let dest = Rectangle::new(x, y, size.0.x, size.0.y);
+            draw_sliced(d, sprite, cap, dest, bar_color);
+        }
+        None => d.draw_rectangle(
+            x as i32,
+            y as i32,
+            size.0.x as i32,
+            size.0.y as i32,
+            bar_color,
+        ),
+    }
 
-    // Draw player lives
-    for i in 0..player.life {
-        d.draw_rectangle(
-            20 + 40 * i,
-            screeninfo.height as i32 - 30 + player.ui_display_offset,
-            35,
-            10,
-            Color::LIGHTGRAY,
+    if palette.outlines() {
+        d.draw_rectangle_lines(
+            x as i32,
+            y as i32,
+            size.0.x as i32,
+            size.0.y as i32,
+            Color::BLACK,
         );
     }
 
-    // Display player score
-    d.draw_text(
-        &format!("Score: {}", player.score),
-        0,
-        player.ui_display_offset,
-        20,
-        color.0,
+    draw_player_mark(
+        d,
+        player.index,
+        position.0.x as i32,
+        position.0.y as i32,
+        (size.0.y / 3.0) as i32,
+        Color::WHITE,
     );
 }
 
 impl PlayerBundle {
     /// Initialize the player (prepare it for the game).
-    pub fn new(
-        rl: &RaylibHandle,
-        ui_display_offset: i32,
-        color: Color,
-        ball_color: Color,
-        controls: PlayerControls,
-    ) -> Self {
+    pub fn new(screeninfo: &ScreenInfo, index: usize, color: Color, ball_color: Color) -> Self {
         Self {
             position: Position(Vector2::new(
-                rl.get_screen_width() as f32 / 2.0,
-                rl.get_screen_height() as f32 * 7.0 / 8.0,
+                screeninfo.width / 2.0,
+                screeninfo.height * 7.0 / 8.0,
             )),
-            size: Size(Vector2::new(rl.get_screen_width() as f32 / 10.0, 20.0)),
+            size: Size(Vector2::new(screeninfo.width / 10.0, 20.0)),
             player: Player {
+                index,
                 life: PLAYER_MAX_LIFE,
-                ui_display_offset,
                 score: 0,
                 ball_color,
+                stunned: 0,
+                combo: 0,
             },
-            controls,
             color: Colored(color),
         }
     }
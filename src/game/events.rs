@@ -0,0 +1,78 @@
+//! Gameplay events, sent by the systems as things happen.
+//!
+//! They are read by the scoring and sound systems during the step, then drained
+//! by `Game` for the particles, the statistics, the level script and the
+//! achievements, so that everything reacts to the same stream.
+
+use bevy_ecs::{entity::Entity, event::Event, schedule::SystemSet};
+use nalgebra::Vector2;
+
+/// Systems sending gameplay events, the systems reading them run after this set.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GameplayEventSenders;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameplayEventKind {
+    BrickDestroyed,
+    /// A ball fell out of the field.
+    BallLost,
+    PaddleHit,
+    /// A life was spent to serve a new ball.
+    LifeLost,
+    /// A player has no life left and leaves the game.
+    PlayerEliminated,
+    /// Every breakable brick of the stage is gone.
+    StageCleared,
+    /// A player won a versus match.
+    MatchWon,
+}
+
+/// What made the event happen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cause {
+    /// A ball hit the brick or the paddle.
+    Ball,
+    /// A neighbouring explosive brick went off.
+    Explosion,
+    /// A ball fell below the paddles.
+    Fall,
+    /// The last life of the player was spent.
+    NoLivesLeft,
+    /// The last breakable brick was destroyed.
+    LastBrick,
+    /// The player won enough rounds.
+    Rounds,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GameplayEvent {
+    pub kind: GameplayEventKind,
+    /// Brick, ball or paddle the event is about.
+    pub entity: Option<Entity>,
+    /// Index of the player concerned, for a brick the one owning the ball.
+    pub player: Option<usize>,
+    pub position: Vector2<f32>,
+    pub cause: Cause,
+}
+
+impl GameplayEvent {
+    pub fn new(kind: GameplayEventKind, cause: Cause, position: Vector2<f32>) -> Self {
+        Self {
+            kind,
+            entity: None,
+            player: None,
+            position,
+            cause,
+        }
+    }
+
+    pub fn entity(mut self, entity: Entity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    pub fn player(mut self, player: usize) -> Self {
+        self.player = Some(player);
+        self
+    }
+}
@@ -0,0 +1,251 @@
+//! Levels built from a seed, for the endless mode and the level of the day.
+//!
+//! The same seed and difficulty always give the same level. The ball has to be
+//! able to get to every breakable brick from below, so steel bricks walling
+//! some of them off are turned into normal ones.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use fastrand::Rng;
+
+use super::{
+    level::{BrickKind, Level, LevelBrick, BRICK_COLORS},
+    BRICKS_PER_LINE,
+};
+
+/// Rows of bricks of the hardest levels, leaving room for the paddles below.
+const MAX_ROWS: usize = 10;
+
+/// Difficulty of the level of the day.
+const DAILY_DIFFICULTY: u32 = 6;
+
+type Grid = Vec<Vec<Option<LevelBrick>>>;
+
+/// Shape the bricks are laid out in.
+#[derive(Clone, Copy)]
+enum Pattern {
+    Noise,
+    Stripes,
+    Checker,
+    Pyramid,
+    Diamond,
+    Frame,
+}
+
+impl Pattern {
+    const ALL: [Pattern; 6] = [
+        Pattern::Noise,
+        Pattern::Stripes,
+        Pattern::Checker,
+        Pattern::Pyramid,
+        Pattern::Diamond,
+        Pattern::Frame,
+    ];
+
+    fn contains(self, column: usize, row: usize, rows: usize) -> bool {
+        let half = BRICKS_PER_LINE as f32 / 2.0;
+        // Distance to the vertical axis, from 0 to 1.
+        let dx = (column as f32 + 0.5 - half).abs() / half;
+
+        match self {
+            Pattern::Noise => true,
+            Pattern::Stripes => row.is_multiple_of(2),
+            Pattern::Checker => (row + column).is_multiple_of(2),
+            Pattern::Pyramid => dx <= (row + 1) as f32 / rows as f32,
+            Pattern::Diamond => {
+                let half_rows = rows as f32 / 2.0;
+                dx + (row as f32 + 0.5 - half_rows).abs() / half_rows <= 1.0
+            }
+            Pattern::Frame => {
+                row == 0 || row == rows - 1 || column == 0 || column == BRICKS_PER_LINE - 1
+            }
+        }
+    }
+}
+
+/// How the layout is mirrored.
+#[derive(Clone, Copy)]
+enum Symmetry {
+    None,
+    /// Left half mirrored on the right.
+    Mirror,
+    /// Top half rotated by 180° on the bottom.
+    Point,
+}
+
+impl Symmetry {
+    const ALL: [Symmetry; 3] = [Symmetry::None, Symmetry::Mirror, Symmetry::Point];
+
+    fn apply(self, bricks: &mut Grid) {
+        let rows = bricks.len();
+
+        match self {
+            Symmetry::None => (),
+            Symmetry::Mirror => {
+                for line in bricks.iter_mut() {
+                    for column in BRICKS_PER_LINE / 2..BRICKS_PER_LINE {
+                        line[column] = line[BRICKS_PER_LINE - 1 - column];
+                    }
+                }
+            }
+            Symmetry::Point => {
+                let cells = rows * BRICKS_PER_LINE;
+
+                for i in cells / 2..cells {
+                    let source = cells - 1 - i;
+                    bricks[i / BRICKS_PER_LINE][i % BRICKS_PER_LINE] =
+                        bricks[source / BRICKS_PER_LINE][source % BRICKS_PER_LINE];
+                }
+            }
+        }
+    }
+}
+
+/// Build a level from a seed. Higher difficulties give more rows, fuller
+/// layouts and more kinds of bricks.
+pub fn generate(name: &str, seed: u64, difficulty: u32) -> Level {
+    let mut rng = Rng::with_seed(seed);
+
+    let rows = (3 + difficulty as usize / 2).min(MAX_ROWS);
+    let density = (0.5 + 0.05 * difficulty as f32).min(0.9);
+
+    // Colours used by this level.
+    let mut palette: Vec<u8> = (0..BRICK_COLORS.len() as u8).collect();
+    rng.shuffle(&mut palette);
+    palette.truncate((2 + difficulty as usize).min(BRICK_COLORS.len()));
+
+    // Chances of a brick to be special.
+    let steel = (0.02 * difficulty as f32).min(0.15);
+    let explosive = (0.05 + 0.01 * difficulty as f32).min(0.1);
+    let moving = (0.03 * difficulty as f32).min(0.1);
+
+    let pattern = Pattern::ALL[rng.usize(..Pattern::ALL.len())];
+    let symmetry = Symmetry::ALL[rng.usize(..Symmetry::ALL.len())];
+
+    let mut bricks: Grid = vec![vec![None; BRICKS_PER_LINE]; rows];
+
+    for (row, line) in bricks.iter_mut().enumerate() {
+        for (column, brick) in line.iter_mut().enumerate() {
+            if !pattern.contains(column, row, rows) || rng.f32() >= density {
+                continue;
+            }
+
+            let color = match pattern {
+                Pattern::Noise => rng.usize(..palette.len()),
+                Pattern::Checker => column / 4 + row,
+                _ => row,
+            };
+
+            let kind = match rng.f32() {
+                roll if roll < steel => BrickKind::Steel,
+                roll if roll < steel + explosive => BrickKind::Explosive,
+                roll if roll < steel + explosive + moving => BrickKind::Moving,
+                _ => BrickKind::Normal,
+            };
+
+            *brick = Some(LevelBrick::new(kind, palette[color % palette.len()]));
+        }
+    }
+
+    symmetry.apply(&mut bricks);
+    make_reachable(&mut bricks);
+
+    // A level needs at least a brick to be cleared.
+    if !bricks
+        .iter()
+        .flatten()
+        .any(|brick| brick.is_some_and(|brick| brick.kind.is_breakable()))
+    {
+        bricks[0][BRICKS_PER_LINE / 2] = Some(LevelBrick::new(BrickKind::Normal, palette[0]));
+    }
+
+    Level {
+        name: name.to_string(),
+        bricks,
+        script: None,
+    }
+}
+
+fn is_steel(bricks: &Grid, (row, column): (usize, usize)) -> bool {
+    bricks[row][column].is_some_and(|brick| !brick.kind.is_breakable())
+}
+
+/// Cells next to a cell, without the diagonals.
+fn neighbours(rows: usize, (row, column): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    [
+        (row.wrapping_sub(1), column),
+        (row + 1, column),
+        (row, column.wrapping_sub(1)),
+        (row, column + 1),
+    ]
+    .into_iter()
+    .filter(move |&(row, column)| row < rows && column < BRICKS_PER_LINE)
+}
+
+/// Cells the ball can get to from below the bricks, breaking its way through
+/// every brick but the steel ones.
+fn reachable(bricks: &Grid) -> Vec<Vec<bool>> {
+    let rows = bricks.len();
+    let mut reached = vec![vec![false; BRICKS_PER_LINE]; rows];
+    let mut pending: Vec<(usize, usize)> = (0..BRICKS_PER_LINE)
+        .map(|column| (rows - 1, column))
+        .collect();
+
+    while let Some(cell) = pending.pop() {
+        if reached[cell.0][cell.1] || is_steel(bricks, cell) {
+            continue;
+        }
+
+        reached[cell.0][cell.1] = true;
+        pending.extend(neighbours(rows, cell));
+    }
+
+    reached
+}
+
+/// Turn steel bricks into normal ones until every breakable brick can be reached.
+fn make_reachable(bricks: &mut Grid) {
+    let rows = bricks.len();
+    let cells = || (0..rows).flat_map(|row| (0..BRICKS_PER_LINE).map(move |column| (row, column)));
+
+    loop {
+        let reached = reachable(bricks);
+        let is_reached = |(row, column): (usize, usize)| reached[row][column];
+
+        if cells().all(|cell| {
+            is_reached(cell) || bricks[cell.0][cell.1].is_none() || is_steel(bricks, cell)
+        }) {
+            return;
+        }
+
+        // Steel bricks the ball can hit, the bottom row being open.
+        let mut walls = cells().filter(|&cell| {
+            is_steel(bricks, cell) && (cell.0 == rows - 1 || neighbours(rows, cell).any(is_reached))
+        });
+
+        // Prefer a wall with something to free behind it.
+        let wall = walls
+            .clone()
+            .find(|&cell| {
+                neighbours(rows, cell).any(|next| !is_reached(next) && !is_steel(bricks, next))
+            })
+            .or_else(|| walls.next());
+
+        let Some((row, column)) = wall else {
+            return;
+        };
+
+        if let Some(brick) = &mut bricks[row][column] {
+            brick.kind = BrickKind::Normal;
+        }
+    }
+}
+
+/// Level shared by every player during a day.
+pub fn daily_level() -> Level {
+    let day = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+
+    generate("Niveau du jour", day, DAILY_DIFFICULTY)
+}
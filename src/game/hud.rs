@@ -0,0 +1,201 @@
+//! Heads-up display: a panel per player anchored to a corner of the screen,
+//! the first two players at the top and the next two at the bottom, with the
+//! stage and the time played at the top centre.
+//!
+//! Sizes are given for a 768 pixels high screen and scaled to the actual one.
+
+use raylib::{
+    core::{
+        drawing::{RaylibDraw, RaylibDrawHandle},
+        text::measure_text,
+    },
+    ffi::Color,
+};
+
+use super::{
+    entities::player::{draw_player_mark, Player},
+    palette::Palette,
+    resources::ScreenInfo,
+};
+
+const REFERENCE_HEIGHT: f32 = 768.0;
+
+const MARGIN: i32 = 10;
+const FONT_SIZE: i32 = 20;
+const LINE_HEIGHT: i32 = 26;
+const LIFE_WIDTH: i32 = 24;
+const LIFE_HEIGHT: i32 = 8;
+const LIFE_GAP: i32 = 5;
+
+/// Lives drawn one by one, more are written as a number.
+const MAX_LIFE_BARS: i32 = 8;
+
+/// What the panel of a player shows.
+pub struct PlayerPanel<'a> {
+    pub player: &'a Player,
+    pub downed: bool,
+    /// Lives are shown with the mode status when shared by the team.
+    pub show_lives: bool,
+}
+
+enum Row {
+    Score(String),
+    Lives(i32),
+    Text(String),
+}
+
+pub struct Hud {
+    scale: f32,
+    width: i32,
+    height: i32,
+}
+
+impl Hud {
+    pub fn new(screeninfo: &ScreenInfo) -> Self {
+        Self {
+            scale: screeninfo.height / REFERENCE_HEIGHT,
+            width: screeninfo.width as i32,
+            height: screeninfo.height as i32,
+        }
+    }
+
+    /// Size on this screen of `size` pixels on the reference one.
+    fn px(&self, size: i32) -> i32 {
+        (size as f32 * self.scale).round() as i32
+    }
+
+    pub fn draw_fps(&self, d: &RaylibDrawHandle) {
+        d.draw_fps(self.width / 2 - self.px(40), self.height - self.px(30));
+    }
+
+    /// Stage and time played, with the status of the mode below.
+    pub fn draw_header(
+        &self,
+        d: &RaylibDrawHandle,
+        stage: &str,
+        seconds: Option<u32>,
+        status: Option<&str>,
+    ) {
+        let header = match seconds {
+            Some(seconds) => format!("{stage}  -  {:02}:{:02}", seconds / 60, seconds % 60),
+            None => stage.to_string(),
+        };
+
+        let (font, margin) = (self.px(FONT_SIZE), self.px(MARGIN));
+
+        for (line, text) in [Some(header.as_str()), status].into_iter().enumerate() {
+            if let Some(text) = text {
+                d.draw_text(
+                    text,
+                    (self.width - measure_text(text, font)) / 2,
+                    margin + line as i32 * self.px(LINE_HEIGHT),
+                    font,
+                    Color::DARKGRAY,
+                );
+            }
+        }
+    }
+
+    pub fn draw_panel(&self, d: &RaylibDrawHandle, palette: Palette, panel: PlayerPanel) {
+        let player = panel.player;
+        let color = palette.resolve(player.ball_color);
+        let (font, line, margin) = (self.px(FONT_SIZE), self.px(LINE_HEIGHT), self.px(MARGIN));
+
+        let mut rows = vec![Row::Score(format!(
+            "J{}  {}",
+            player.index + 1,
+            player.score
+        ))];
+
+        if panel.show_lives {
+            rows.push(Row::Lives(player.life.max(0)));
+        }
+
+        if player.combo > 1 {
+            rows.push(Row::Text(format!(
+                "Combo {} (x{})",
+                player.combo,
+                player.multiplier()
+            )));
+        }
+
+        if player.stunned > 0 {
+            rows.push(Row::Text("Étourdi".to_string()));
+        }
+
+        if panel.downed {
+            rows.push(Row::Text("À terre".to_string()));
+        }
+
+        let lives_text = |lives: i32| format!("Vies : {lives}");
+        let life_step = self.px(LIFE_WIDTH + LIFE_GAP);
+
+        let row_width = |row: &Row| match row {
+            // Room for the mark before the score.
+            Row::Score(text) => font + measure_text(text, font),
+            Row::Lives(lives) if *lives > MAX_LIFE_BARS => measure_text(&lives_text(*lives), font),
+            Row::Lives(lives) => lives * life_step,
+            Row::Text(text) => measure_text(text, font),
+        };
+
+        let width = rows.iter().map(row_width).max().unwrap_or(0);
+        let height = rows.len() as i32 * line;
+
+        let right = player.index % 2 == 1;
+        let bottom = player.index % 4 >= 2;
+
+        let x = if right {
+            self.width - margin - width
+        } else {
+            margin
+        };
+        let y = if bottom {
+            self.height - margin - height
+        } else {
+            margin
+        };
+
+        d.draw_rectangle(
+            x - margin / 2,
+            y - margin / 2,
+            width + margin,
+            height + margin,
+            Color::RAYWHITE.fade(0.7),
+        );
+
+        for (index, row) in rows.iter().enumerate() {
+            // Rows of the panels on the right are aligned on the edge of the screen.
+            let row_x = if right { x + width - row_width(row) } else { x };
+            let row_y = y + index as i32 * line;
+
+            match row {
+                Row::Score(text) => {
+                    draw_player_mark(
+                        d,
+                        player.index,
+                        row_x + font / 2,
+                        row_y + font / 2,
+                        font / 3,
+                        color,
+                    );
+                    d.draw_text(text, row_x + font, row_y, font, color);
+                }
+                Row::Lives(lives) if *lives > MAX_LIFE_BARS => {
+                    d.draw_text(&lives_text(*lives), row_x, row_y, font, Color::GRAY);
+                }
+                Row::Lives(lives) => {
+                    for life in 0..*lives {
+                        d.draw_rectangle(
+                            row_x + life * life_step,
+                            row_y + (font - self.px(LIFE_HEIGHT)) / 2,
+                            self.px(LIFE_WIDTH),
+                            self.px(LIFE_HEIGHT),
+                            Color::GRAY,
+                        );
+                    }
+                }
+                Row::Text(text) => d.draw_text(text, row_x, row_y, font, color),
+            }
+        }
+    }
+}
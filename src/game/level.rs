@@ -0,0 +1,236 @@
+use raylib::ffi::Color;
+
+use super::{palette::CHECKER_COLOR, BRICKS_PER_LINE};
+
+pub use super::entities::brick::{draw_brick_kind, BrickKind};
+
+/// Lines of bricks of the default level.
+const DEFAULT_LINES: usize = 5;
+
+/// Name of the default level.
+pub const DEFAULT_LEVEL_NAME: &str = "Classique";
+
+/// Line separating the layout of a level from the colours of its bricks.
+const COLORS_SEPARATOR: &str = "---";
+
+/// Colours a brick may have, by index. The first one is the default checkerboard.
+pub const BRICK_COLORS: [Color; 10] = [
+    Color::GRAY,
+    Color::RED,
+    Color::ORANGE,
+    Color::GOLD,
+    Color::GREEN,
+    Color::SKYBLUE,
+    Color::BLUE,
+    Color::PURPLE,
+    Color::PINK,
+    Color::BROWN,
+];
+
+/// Colour of the brick at a cell of the grid.
+pub fn brick_color(color: u8, column: usize, row: usize) -> Color {
+    match color {
+        0 if !(row + column).is_multiple_of(2) => CHECKER_COLOR,
+        _ => BRICK_COLORS[color as usize % BRICK_COLORS.len()],
+    }
+}
+
+/// Character of each kind of brick in the layout of a level.
+const KIND_CHARS: [(char, BrickKind); 4] = [
+    ('#', BrickKind::Normal),
+    ('*', BrickKind::Explosive),
+    ('=', BrickKind::Steel),
+    ('~', BrickKind::Moving),
+];
+
+/// Brick of a level.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct LevelBrick {
+    pub kind: BrickKind,
+    /// Index in `BRICK_COLORS`.
+    pub color: u8,
+}
+
+impl LevelBrick {
+    pub fn new(kind: BrickKind, color: u8) -> Self {
+        Self { kind, color }
+    }
+}
+
+/// Layout of the bricks of a stage.
+///
+/// Levels are written as text, one line per row of bricks from the top:
+/// `#` is a brick, `*` an explosive one, `=` a steel one, `~` a moving one
+/// and `.` or a space is an empty slot. The layout may be followed
+/// by a `---` line and a grid of the same shape giving the colour of each brick
+/// as a digit (see `BRICK_COLORS`).
+#[derive(Clone)]
+pub struct Level {
+    pub name: String,
+
+    /// Rows of bricks.
+    pub bricks: Vec<Vec<Option<LevelBrick>>>,
+
+    /// Source of the Rhai script of the level, see `script`.
+    pub script: Option<String>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_LEVEL_NAME.to_string(),
+            bricks: vec![
+                vec![Some(LevelBrick::new(BrickKind::Normal, 0)); BRICKS_PER_LINE];
+                DEFAULT_LINES
+            ],
+            script: None,
+        }
+    }
+}
+
+impl Level {
+    pub fn empty(name: String) -> Self {
+        Self {
+            name,
+            bricks: vec![],
+            script: None,
+        }
+    }
+
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+
+        let layout: Vec<(usize, &str)> = lines
+            .by_ref()
+            .take_while(|(_, line)| line.trim_end() != COLORS_SEPARATOR)
+            .collect();
+
+        let colors: Vec<(usize, &str)> = lines.collect();
+
+        let mut bricks = layout
+            .iter()
+            .map(|(i, line)| {
+                if line.chars().count() > BRICKS_PER_LINE {
+                    return Err(format!(
+                        "line {} has more than {BRICKS_PER_LINE} bricks",
+                        i + 1
+                    ));
+                }
+
+                line.chars()
+                    .map(|c| match c {
+                        '.' | ' ' => Ok(None),
+                        _ => KIND_CHARS
+                            .iter()
+                            .find(|(kind_char, _)| *kind_char == c)
+                            .map(|(_, kind)| Some(LevelBrick::new(*kind, 0)))
+                            .ok_or_else(|| format!("unexpected '{c}' on line {}", i + 1)),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<Option<LevelBrick>>>, String>>()?;
+
+        for (row, (i, line)) in colors.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let color = match c {
+                    '.' | ' ' => continue,
+                    _ => c
+                        .to_digit(10)
+                        .ok_or_else(|| format!("unexpected colour '{c}' on line {}", i + 1))?,
+                };
+
+                if let Some(Some(brick)) = bricks.get_mut(row).and_then(|line| line.get_mut(column))
+                {
+                    brick.color = color as u8;
+                }
+            }
+        }
+
+        let level = Self {
+            name: name.to_string(),
+            bricks,
+            script: None,
+        };
+
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Check that the level can be played, `parse` rejects the levels that can't.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self
+            .bricks
+            .iter()
+            .flatten()
+            .any(|brick| brick.is_some_and(|brick| brick.kind.is_breakable()))
+        {
+            return Err("no brick to break".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Write the level in the format read by `parse`.
+    pub fn to_text(&self) -> String {
+        // Empty rows at the bottom are useless.
+        let rows = self
+            .bricks
+            .iter()
+            .rposition(|line| line.iter().any(Option::is_some))
+            .map_or(0, |last| last + 1);
+
+        let grid = |cell: fn(LevelBrick) -> char| -> String {
+            self.bricks[..rows]
+                .iter()
+                .map(|line| {
+                    let line: String = line.iter().map(|brick| brick.map_or('.', cell)).collect();
+                    format!("{}\n", line.trim_end_matches('.'))
+                })
+                .collect()
+        };
+
+        let mut text = grid(|brick| {
+            KIND_CHARS
+                .iter()
+                .find(|(_, kind)| *kind == brick.kind)
+                .map_or('#', |(kind_char, _)| *kind_char)
+        });
+
+        if self
+            .bricks
+            .iter()
+            .flatten()
+            .any(|brick| brick.is_some_and(|brick| brick.color != 0))
+        {
+            text += COLORS_SEPARATOR;
+            text += "\n";
+            text += &grid(|brick| char::from_digit(brick.color as u32, 10).unwrap_or('0'));
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_levels_are_read_back() {
+        let level = Level::parse("test", "#*\n.=~\n---\n12\n.34\n").unwrap();
+        let text = level.to_text();
+
+        assert_eq!(text, "#*\n.=~\n---\n12\n.34\n");
+        assert_eq!(Level::parse("test", &text).unwrap().to_text(), text);
+    }
+
+    #[test]
+    fn levels_without_brick_to_break_are_rejected() {
+        let mut level = Level::empty("test".to_string());
+        assert!(level.validate().is_err());
+
+        level.bricks = vec![vec![Some(LevelBrick::new(BrickKind::Steel, 0)); 3]];
+        assert!(level.validate().is_err());
+        assert!(Level::parse("test", &level.to_text()).is_err());
+    }
+}
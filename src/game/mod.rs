@@ -1,60 +1,207 @@
+pub mod ai;
 mod audio;
+pub mod bench;
 mod collisions;
 mod components;
 mod entities;
+pub mod events;
+pub mod generator;
+mod hud;
+pub mod level;
+pub mod net;
+pub mod palette;
+mod particles;
 mod resources;
+pub mod rules;
+pub mod save;
+mod script;
+pub mod stats;
 
-use bevy_ecs::{event::Events, query::With, schedule::Schedule, world::World};
+use std::collections::HashMap;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::Events,
+    query::{Has, Or, With, Without},
+    schedule::{common_conditions::resource_exists, IntoSystemConfigs, Schedule},
+    world::World,
+};
 use nalgebra::Vector2;
-use raylib::{ffi::KeyboardKey, prelude::*, core::text::measure_text};
+use raylib::{core::text::measure_text, ffi::KeyboardKey, prelude::*};
 
-use crate::assets::Assets;
+use crate::assets::{Assets, SoundBank};
 
 use self::{
-    audio::AudioEvent,
-    collisions::{collisions_ball_borders, collisions_ball_bricks, collisions_ball_player},
-    components::{Colored, Position, Size},
+    ai::{ai_movement, AiController, AiDifficulty},
+    audio::{gameplay_sounds, AudioEvent},
+    collisions::{
+        brick_grid_update, collisions_ball_borders, collisions_ball_bricks, collisions_ball_player,
+    },
+    components::{Colored, Position, Size, Velocity},
     entities::ball::{
-        ball_reset_event, ball_respawning, draw_ball, update_ball_velocity, Ball, BallResetEvent,
+        ball_reset_event, ball_respawning, draw_ball, update_ball_velocity, Ball, BallBundle,
+        BallResetEvent,
     },
-    entities::brick::{draw_brick, Brick, BrickBundle},
+    entities::brick::{
+        brick_explosions, brick_sliding, draw_brick, Brick, BrickBundle, BrickKind, Detonating,
+        Sliding, SLIDING_SPEED,
+    },
+    entities::hazard::{collisions_hazard_player, draw_hazard, hazard_movement, Hazard},
     entities::player::{
-        ball_retaining_logic, draw_player, player_death, player_movement_logic, Player,
+        ball_retaining_logic, draw_player, player_death, player_movement_logic, Downed, Player,
         PlayerBundle, PlayerControls,
     },
-    resources::{InputManager, ScreenInfo},
+    events::{Cause, GameplayEvent, GameplayEventKind, GameplayEventSenders},
+    hud::{Hud, PlayerPanel},
+    level::{brick_color, Level, LevelBrick},
+    net::{Client, Host, NetRole},
+    palette::{Palette, PLAYER_COLORS},
+    particles::Particles,
+    resources::{BrickGrid, InputManager, ScreenInfo},
+    rules::{
+        brick_scoring, coop_revive, coop_stage_logic, versus_attacks, versus_round_logic,
+        CoopState, Difficulty, GameMode, MatchResults, RoundPhase, ScoreEvent, Tuning, VersusState,
+        WinCondition,
+    },
+    save::SaveGame,
+    script::{LevelScript, ScriptCommand},
+    stats::SessionStats,
 };
 
-const LINES_OF_BRICKS: usize = 5;
-const BRICKS_PER_LINE: usize = 20;
+pub const BRICKS_PER_LINE: usize = 20;
+pub const BRICK_HEIGHT: f32 = 40.0;
+
+/// Height of the first line of bricks.
+pub const INITIAL_DOWN_POSITION: f32 = 50.0;
+
+/// Lines of bricks a level may fill, lower ones would get in the way of the paddles.
+pub const MAX_BRICK_LINES: usize = 12;
+
+/// Frames the GAME OVER screen stays before going back to the menu.
+const GAME_OVER_DELAY: u32 = 300;
+
+/// Rounds a player has to win to win a versus match.
+const VERSUS_ROUNDS_TO_WIN: u32 = 2;
 
 pub struct Game {
     brick_size: Vector2<f32>,
+    mode: GameMode,
+    win_condition: WinCondition,
+    difficulty: Difficulty,
+    tuning: Tuning,
+    palette: Palette,
+
+    /// Levels played in turn, the current one is `levels[level % levels.len()]`.
+    levels: Vec<Level>,
+    level: usize,
+
+    /// Seed of the endless mode, where levels are generated as the game goes.
+    endless_seed: Option<u64>,
+
+    /// Script of the current level.
+    script: Option<LevelScript>,
+
+    /// Whether breakable bricks were seen on the current stage, those without
+    /// any (only steel bricks, or an empty grid) are never cleared.
+    stage_clearable: bool,
+
+    /// Players driven by the computer (by index).
+    cpu_players: HashMap<usize, AiDifficulty>,
+
+    net: Option<NetRole>,
+
+    /// Statistics of the game, not kept by network clients.
+    stats: Option<SessionStats>,
+
+    /// Gameplay events of the human players since the last `take_events`.
+    events: Vec<GameplayEvent>,
+
+    particles: Particles,
+
+    /// Score of the player (or the team) as of the last step.
+    score: u32,
+
+    /// Frames elapsed since the game was lost.
+    game_over_timer: u32,
+
     world: World,
     schedule: Schedule,
 }
 
-impl Default for Game {
-    fn default() -> Game {
-        let brick_size = Vector2::default();
+/// Systems run at each step of the game, also timed by the benchmark.
+fn gameplay_schedule() -> Schedule {
+    let mut schedule = Schedule::default();
 
-        let world = World::new();
-        let mut schedule = Schedule::default();
+    schedule.add_systems((
+        update_ball_velocity,
+        ball_retaining_logic,
+        brick_grid_update,
+        player_movement_logic,
+        ball_reset_event,
+        hazard_movement,
+        collisions_hazard_player,
+        ai_movement,
+        brick_sliding,
+    ));
 
-        schedule.add_systems((
-            update_ball_velocity,
-            ball_retaining_logic,
+    schedule.add_systems(
+        (
             collisions_ball_borders,
-            collisions_ball_bricks,
+            collisions_ball_bricks.after(brick_grid_update),
             player_death,
             collisions_ball_player,
-            player_movement_logic,
             ball_respawning,
-            ball_reset_event,
-        ));
+            brick_explosions,
+        )
+            .in_set(GameplayEventSenders),
+    );
+
+    schedule.add_systems((brick_scoring, gameplay_sounds).after(GameplayEventSenders));
+
+    schedule.add_systems(
+        (
+            versus_attacks,
+            versus_round_logic
+                .after(player_death)
+                .in_set(GameplayEventSenders),
+        )
+            .run_if(resource_exists::<VersusState>()),
+    );
+
+    schedule.add_systems(
+        (coop_revive.in_set(GameplayEventSenders), coop_stage_logic)
+            .run_if(resource_exists::<CoopState>()),
+    );
+
+    schedule
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        let brick_size = Vector2::default();
+
+        let world = World::new();
+        let schedule = gameplay_schedule();
 
         Game {
             brick_size,
+            mode: GameMode::Solo,
+            win_condition: WinCondition::LastStanding,
+            difficulty: Difficulty::default(),
+            tuning: Tuning::default(),
+            levels: vec![Level::default()],
+            palette: Palette::default(),
+            level: 0,
+            endless_seed: None,
+            script: None,
+            stage_clearable: false,
+            cpu_players: HashMap::new(),
+            net: None,
+            stats: None,
+            events: vec![],
+            particles: Particles::default(),
+            score: 0,
+            game_over_timer: 0,
             schedule,
             world,
         }
@@ -62,97 +209,673 @@ impl Default for Game {
 }
 
 impl Game {
-    pub fn init(&mut self, rl: &RaylibHandle, two_players: bool) {
+    pub fn init(&mut self, rl: &RaylibHandle, mode: GameMode) {
+        self.init_resources(rl, mode);
+
+        match mode {
+            GameMode::Solo => (),
+            GameMode::Versus => self.world.insert_resource(VersusState::new(
+                mode.player_count(),
+                self.win_condition,
+                VERSUS_ROUNDS_TO_WIN,
+            )),
+            GameMode::Coop => self
+                .world
+                .insert_resource(CoopState::new(mode.player_count())),
+        }
+
+        self.spawn_players();
+        self.start_stage();
+        self.stats = Some(SessionStats::new(mode, self.difficulty));
+    }
+
+    /// Go on with a saved game.
+    pub fn resume(&mut self, rl: &RaylibHandle, mut save: SaveGame) {
+        let levels: Vec<Level> = save
+            .levels
+            .iter()
+            .filter_map(|level| level.to_level())
+            .collect();
+
+        let mode = match save.coop {
+            Some(_) => GameMode::Coop,
+            None => GameMode::Solo,
+        };
+
+        for player in &save.players {
+            if let Some(difficulty) = player.cpu {
+                self.cpu_players.insert(player.index, difficulty);
+            }
+        }
+
+        self.init_resources(rl, mode);
+        self.set_levels(levels);
+        self.level = save.level;
+        self.endless_seed = save.endless_seed;
+        self.score = save.score;
+
+        save.restore(&mut self.world);
+        self.stats = Some(
+            save.stats
+                .take()
+                .unwrap_or_else(|| SessionStats::new(mode, self.difficulty)),
+        );
+
+        // The script starts over, without running its top level and `on_stage_start` again.
+        let level = &self.levels[self.level % self.levels.len()];
+
+        self.script = level
+            .script
+            .as_deref()
+            .and_then(|source| LevelScript::load(&level.name, source));
+    }
+
+    /// State of the game to write when quitting, `None` for the games that
+    /// can't be resumed: network and versus games, or lost ones.
+    pub fn save(&mut self) -> Option<SaveGame> {
+        if self.net.is_some() || self.mode == GameMode::Versus || self.game_over_timer > 0 {
+            return None;
+        }
+
+        Some(SaveGame::capture(
+            &mut self.world,
+            self.level,
+            &self.levels,
+            self.endless_seed,
+            self.score,
+            self.difficulty,
+            self.stats.clone(),
+        ))
+    }
+
+    /// Initialize a game mirroring the one of a remote host.
+    pub fn init_client(&mut self, rl: &RaylibHandle, client: Client) {
+        // The client only uses the keys of the first player.
+        self.init_resources(rl, GameMode::Solo);
+        self.net = Some(NetRole::Client(client));
+    }
+
+    /// Rule deciding who wins a versus round, must be called before `init`.
+    pub fn set_win_condition(&mut self, condition: WinCondition) {
+        self.win_condition = condition;
+    }
+
+    /// Difficulty recorded with the score, its constants are set by `set_tuning`.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Gameplay constants, must be called before `init`.
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+    }
+
+    /// Colours to draw the game with, can be changed at any time.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Levels to play in turn, must be called before `init`.
+    pub fn set_levels(&mut self, levels: Vec<Level>) {
+        if !levels.is_empty() {
+            self.levels = levels;
+        }
+    }
+
+    /// Play generated levels of increasing difficulty, must be called before `init`.
+    pub fn set_endless(&mut self, seed: u64) {
+        self.levels.clear();
+        self.endless_seed = Some(seed);
+    }
+
+    /// Let the computer drive a player, must be called before `init`.
+    pub fn set_cpu_player(&mut self, index: usize, difficulty: AiDifficulty) {
+        self.cpu_players.insert(index, difficulty);
+    }
+
+    /// Let remote players join this game.
+    pub fn set_host(&mut self, host: Host) {
+        self.net = Some(NetRole::Host(host));
+    }
+
+    fn init_resources(&mut self, rl: &RaylibHandle, mode: GameMode) {
         let (width, height) = (rl.get_screen_width() as f32, rl.get_screen_height() as f32);
-        self.brick_size = Vector2::new(rl.get_screen_width() as f32 / BRICKS_PER_LINE as f32, 40.0);
+        self.brick_size = Vector2::new(
+            rl.get_screen_width() as f32 / BRICKS_PER_LINE as f32,
+            BRICK_HEIGHT,
+        );
+        self.mode = mode;
 
-        // Spawn players
         let mut input_manager = InputManager::default();
 
-        self.world.spawn(PlayerBundle::new(
-            rl,
-            0,
-            Color::BLACK.fade(0.5),
-            Color::MAROON,
-            PlayerControls {
-                left: KeyboardKey::KEY_LEFT,
-                right: KeyboardKey::KEY_RIGHT,
-                launch: KeyboardKey::KEY_R,
-            },
-        ));
-        input_manager.track(KeyboardKey::KEY_LEFT);
-        input_manager.track(KeyboardKey::KEY_RIGHT);
-        input_manager.track(KeyboardKey::KEY_R);
-
-        if two_players {
-            self.world.spawn(PlayerBundle::new(
-                rl,
-                20,
-                Color::BLUE.fade(0.5),
-                Color::BLUEVIOLET,
-                PlayerControls {
-                    left: KeyboardKey::KEY_A,
-                    right: KeyboardKey::KEY_D,
-                    launch: KeyboardKey::KEY_O,
-                },
-            ));
+        for index in (0..mode.player_count()).filter(|i| !self.cpu_players.contains_key(i)) {
+            let controls = player_controls(index);
 
-            input_manager.track(KeyboardKey::KEY_A);
-            input_manager.track(KeyboardKey::KEY_D);
-            input_manager.track(KeyboardKey::KEY_O);
+            input_manager.track(controls.left);
+            input_manager.track(controls.right);
+            input_manager.track(controls.launch);
         }
 
         self.world.insert_resource(input_manager);
         self.world.insert_resource(ScreenInfo { width, height });
+        self.world.insert_resource(BrickGrid::new(self.brick_size));
+        self.world.insert_resource(self.tuning);
         self.world.insert_resource(Events::<AudioEvent>::default());
         self.world
             .insert_resource(Events::<BallResetEvent>::default());
+        self.world
+            .insert_resource(Events::<GameplayEvent>::default());
+        self.world.insert_resource(Events::<ScoreEvent>::default());
+    }
+
+    fn spawn_players(&mut self) {
+        let screeninfo = *self.world.resource::<ScreenInfo>();
+
+        for index in 0..self.mode.player_count() {
+            let (color, ball_color) = PLAYER_COLORS[index.min(PLAYER_COLORS.len() - 1)];
+
+            let mut bundle = PlayerBundle::new(&screeninfo, index, color, ball_color);
+            bundle.player.life = self.tuning.player_lives;
+            bundle.size.0.x = screeninfo.width * self.tuning.paddle_width;
+
+            match self.cpu_players.get(&index) {
+                Some(difficulty) => self.world.spawn((bundle, AiController::new(*difficulty))),
+                None => self.world.spawn((bundle, player_controls(index))),
+            };
+        }
+    }
+
+    /// Clear the field and start a new versus round.
+    fn next_round(&mut self) {
+        let entities: Vec<Entity> = self
+            .world
+            .query_filtered::<Entity, Or<(With<Player>, With<Ball>, With<Brick>, With<Hazard>)>>()
+            .iter(&self.world)
+            .collect();
+
+        for entity in entities {
+            self.world.despawn(entity);
+        }
+
+        self.world.resource_mut::<VersusState>().next_round();
+        self.level += 1;
+
+        self.spawn_players();
+        self.start_stage();
+    }
+
+    /// Whether the game is finished and should go back to the menu.
+    pub fn is_over(&self) -> bool {
+        self.results().is_some() || self.game_over_timer >= GAME_OVER_DELAY
+    }
+
+    /// Whether the game is lost and may be continued.
+    pub fn can_continue(&self) -> bool {
+        self.game_over_timer > 0
+    }
+
+    /// Give another chance after a GAME OVER, the score is kept.
+    pub fn continue_game(&mut self) {
+        self.game_over_timer = 0;
+
+        match self.mode {
+            GameMode::Solo => {
+                self.spawn_players();
+
+                for mut player in self.world.query::<&mut Player>().iter_mut(&mut self.world) {
+                    player.score = self.score;
+                }
+            }
+            GameMode::Coop => self.world.resource_mut::<CoopState>().continue_game(),
+            GameMode::Versus => (),
+        }
+    }
+
+    /// Final score of a lost solo game, to be recorded in the high scores.
+    pub fn final_score(&self) -> Option<u32> {
+        (self.mode == GameMode::Solo && self.game_over_timer > 0).then_some(self.score)
+    }
 
+    /// Whether no player is able to play anymore.
+    fn is_game_over(&mut self) -> bool {
+        match self.mode {
+            GameMode::Solo => self
+                .world
+                .query_filtered::<(), With<Player>>()
+                .iter(&self.world)
+                .next()
+                .is_none(),
+            // Every player is downed and there is no life left to revive them.
+            GameMode::Coop => {
+                self.world.resource::<CoopState>().lives == 0
+                    && self
+                        .world
+                        .query_filtered::<(), (With<Player>, Without<Downed>)>()
+                        .iter(&self.world)
+                        .next()
+                        .is_none()
+            }
+            GameMode::Versus => false,
+        }
+    }
+
+    pub fn stats(&self) -> Option<&SessionStats> {
+        self.stats.as_ref()
+    }
+
+    /// Drain the gameplay events of the human players since the last call.
+    pub fn take_events(&mut self) -> Vec<GameplayEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn results(&self) -> Option<MatchResults> {
+        self.world
+            .get_resource::<VersusState>()
+            .and_then(VersusState::results)
+    }
+
+    /// Build the bricks of the current level and run its script.
+    fn start_stage(&mut self) {
+        if let Some(seed) = self.endless_seed {
+            if self.level >= self.levels.len() {
+                let level = generator::generate(
+                    &format!("Sans fin {}", self.level + 1),
+                    seed.wrapping_add(self.level as u64),
+                    self.level as u32,
+                );
+
+                self.levels.push(level);
+            }
+        }
+
+        self.stage_clearable = false;
         self.reset_bricks();
+
+        let level = &self.levels[self.level % self.levels.len()];
+
+        self.script = level
+            .script
+            .as_deref()
+            .and_then(|source| LevelScript::load(&level.name, source))
+            .and_then(LevelScript::start);
+
+        if let Some(mut script) = self.script.take() {
+            let commands = script.call("on_stage_start", (self.level as i64 + 1,));
+
+            self.script = Some(script);
+            self.apply_script_commands(commands);
+        }
     }
 
     fn reset_bricks(&mut self) {
-        // Initialize bricks
-        const INITIAL_DOWN_POSITION: f32 = 50.0;
+        let level = self.levels[self.level % self.levels.len()].clone();
+
+        for (row, line) in level.bricks.iter().enumerate() {
+            for (column, brick) in line.iter().enumerate() {
+                let Some(brick) = brick else {
+                    continue;
+                };
+
+                let bundle = self.brick_bundle(column, row, *brick);
+
+                if brick.kind != BrickKind::Moving {
+                    self.world.spawn(bundle);
+                    continue;
+                }
+
+                // Moving bricks slide over the empty cells next to them.
+                let sliding = Sliding {
+                    min: bundle.position.0.x
+                        - free_cells(line[..column].iter().rev()) * self.brick_size.x,
+                    max: bundle.position.0.x
+                        + free_cells(line[column + 1..].iter()) * self.brick_size.x,
+                };
+
+                let velocity = if sliding.min < sliding.max {
+                    Vector2::new(SLIDING_SPEED, 0.0)
+                } else {
+                    Vector2::zeros()
+                };
+
+                self.world.spawn((bundle, sliding, Velocity(velocity)));
+            }
+        }
+    }
+
+    fn brick_bundle(&self, column: usize, row: usize, brick: LevelBrick) -> BrickBundle {
+        BrickBundle {
+            position: components::Position(Vector2::new(
+                column as f32 * self.brick_size.x + self.brick_size.x / 2.0,
+                row as f32 * self.brick_size.y + INITIAL_DOWN_POSITION,
+            )),
+            size: components::Size(self.brick_size),
+            color: components::Colored(brick_color(brick.color, column, row)),
+            kind: brick.kind,
+            brick: Brick,
+        }
+    }
+
+    fn breakable_bricks_left(&mut self) -> bool {
+        self.world
+            .query::<&BrickKind>()
+            .iter(&self.world)
+            .any(|kind| kind.is_breakable())
+    }
+
+    /// Cell of the grid of a brick, from its position.
+    fn brick_cell(&self, position: Vector2<f32>) -> (usize, usize) {
+        (
+            (position.x / self.brick_size.x) as usize,
+            ((position.y - INITIAL_DOWN_POSITION) / self.brick_size.y).round() as usize,
+        )
+    }
+
+    fn brick_at(&mut self, column: usize, row: usize) -> Option<Entity> {
+        let cells: Vec<(Entity, (usize, usize))> = self
+            .world
+            .query_filtered::<(Entity, &Position), With<Brick>>()
+            .iter(&self.world)
+            .map(|(entity, position)| (entity, self.brick_cell(position.0)))
+            .collect();
+
+        cells
+            .into_iter()
+            .find(|(_, cell)| *cell == (column, row))
+            .map(|(entity, _)| entity)
+    }
+
+    /// Hand the gameplay events of the last step to the particles, the
+    /// statistics, the level script and the achievements.
+    fn handle_events(&mut self) {
+        let events: Vec<GameplayEvent> = self
+            .world
+            .resource_mut::<Events<GameplayEvent>>()
+            .drain()
+            .collect();
+
+        for event in &events {
+            if event.kind == GameplayEventKind::BrickDestroyed {
+                // Debris of the player who broke the brick, or of the explosion.
+                let color = match (event.cause, event.player) {
+                    (Cause::Ball, Some(player)) => {
+                        PLAYER_COLORS[player.min(PLAYER_COLORS.len() - 1)].1
+                    }
+                    _ => Color::ORANGE,
+                };
+
+                self.particles.burst(event.position, color);
+            }
+
+            if let Some(stats) = &mut self.stats {
+                stats.record(event);
+            }
+        }
+
+        let scores: Vec<ScoreEvent> = self
+            .world
+            .resource_mut::<Events<ScoreEvent>>()
+            .drain()
+            .collect();
 
-        let mut bricks = vec![];
+        for score in scores {
+            let (_, ball_color) = PLAYER_COLORS[score.player.min(PLAYER_COLORS.len() - 1)];
+            self.particles
+                .popup(score.position, score.points, ball_color);
+        }
+
+        // Only the human players make progress, not the demo or the computer.
+        self.events.extend(events.iter().filter(|event| {
+            event
+                .player
+                .is_none_or(|player| !self.cpu_players.contains_key(&player))
+        }));
+
+        let Some(mut script) = self.script.take() else {
+            return;
+        };
+
+        let mut commands = vec![];
+
+        for event in events {
+            // The player may be dead already.
+            let Some(player) = event.player else {
+                continue;
+            };
 
-        for i in 0..LINES_OF_BRICKS {
-            for j in 0..BRICKS_PER_LINE {
-                bricks.push(BrickBundle {
-                    position: components::Position(Vector2::new(
-                        j as f32 * self.brick_size.x + self.brick_size.x / 2.0,
-                        i as f32 * self.brick_size.y + INITIAL_DOWN_POSITION,
-                    )),
-                    size: components::Size(self.brick_size),
-                    color: components::Colored(if (i + j) % 2 == 0 {
-                        Color::GRAY
-                    } else {
-                        Color::LIGHTGRAY
-                    }),
-                    brick: Brick,
-                });
+            match event.kind {
+                GameplayEventKind::BrickDestroyed => {
+                    let (column, row) = self.brick_cell(event.position);
+
+                    commands.extend(script.call(
+                        "on_brick_destroyed",
+                        (player as i64, column as i64, row as i64),
+                    ));
+                }
+                GameplayEventKind::BallLost => {
+                    commands.extend(script.call("on_ball_lost", (player as i64,)));
+                }
+                _ => (),
             }
         }
 
-        self.world.spawn_batch(bricks);
+        self.script = Some(script);
+        self.apply_script_commands(commands);
+    }
+
+    fn apply_script_commands(&mut self, commands: Vec<ScriptCommand>) {
+        for command in commands {
+            match command {
+                ScriptCommand::SpawnBrick { column, row } => {
+                    if column < BRICKS_PER_LINE
+                        && row < MAX_BRICK_LINES
+                        && self.brick_at(column, row).is_none()
+                    {
+                        let bundle =
+                            self.brick_bundle(column, row, LevelBrick::new(BrickKind::Normal, 0));
+                        self.world.spawn(bundle);
+                    }
+                }
+                ScriptCommand::RemoveBrick { column, row } => {
+                    if let Some(entity) = self.brick_at(column, row) {
+                        self.world.despawn(entity);
+                    }
+                }
+                ScriptCommand::AddScore { player, points } => {
+                    for mut target in self.world.query::<&mut Player>().iter_mut(&mut self.world) {
+                        if target.index == player {
+                            target.score += points;
+                        }
+                    }
+                }
+                ScriptCommand::AddLife { player } => {
+                    for mut target in self.world.query::<&mut Player>().iter_mut(&mut self.world) {
+                        if target.index == player {
+                            target.life += 1;
+                        }
+                    }
+                }
+                ScriptCommand::SpawnBall { player } => {
+                    let owner = self
+                        .world
+                        .query::<(Entity, &Position, &Player)>()
+                        .iter(&self.world)
+                        .find(|(_, _, target)| target.index == player)
+                        .map(|(entity, position, target)| {
+                            (entity, Position(position.0), target.ball_color)
+                        });
+
+                    if let Some((entity, position, color)) = owner {
+                        let speed = self.tuning.ball_speed;
+                        let mut ball = BallBundle::new(entity, color, &position);
+
+                        ball.ball.active = true;
+                        ball.velocity.0 = Vector2::new((fastrand::f32() - 0.5) * speed, -speed);
+
+                        self.world.spawn(ball);
+                    }
+                }
+                ScriptCommand::ScaleBallSpeed(factor) => {
+                    for mut velocity in self
+                        .world
+                        .query_filtered::<&mut Velocity, With<Ball>>()
+                        .iter_mut(&mut self.world)
+                    {
+                        velocity.0 *= factor;
+                    }
+                }
+            }
+        }
     }
 
     pub fn update(&mut self, rl: &RaylibHandle, raudio: &RaylibAudio, assets: &Assets) {
         self.world.resource_mut::<InputManager>().update(rl);
 
-        self.schedule.run(&mut self.world);
+        let sounds = match &mut self.net {
+            Some(NetRole::Client(client)) => {
+                // The host runs the game, just send our keys and mirror its state.
+                let input = self.world.resource::<InputManager>();
+                let controls = player_controls(0);
+
+                client.send_input(
+                    input.is_key_down(controls.left),
+                    input.is_key_down(controls.right),
+                    input.is_key_down(controls.launch),
+                );
+
+                match client.receive() {
+                    Some(snapshot) => {
+                        let sounds = snapshot
+                            .sounds
+                            .iter()
+                            .filter_map(|sound| AudioEvent::from_byte(*sound))
+                            .collect();
+
+                        self.mode = net::apply_snapshot(&mut self.world, snapshot);
+                        sounds
+                    }
+                    None => vec![],
+                }
+            }
+            Some(NetRole::Host(host)) => {
+                host.receive(&mut self.world, self.mode.player_count());
+                self.simulate();
 
-        for event in self.world.resource_mut::<Events<AudioEvent>>().drain() {
+                let sounds: Vec<AudioEvent> = self
+                    .world
+                    .resource_mut::<Events<AudioEvent>>()
+                    .drain()
+                    .collect();
+
+                if let Some(NetRole::Host(host)) = &mut self.net {
+                    host.broadcast(&mut self.world, &sounds);
+                }
+
+                sounds
+            }
+            None => {
+                self.simulate();
+
+                self.world
+                    .resource_mut::<Events<AudioEvent>>()
+                    .drain()
+                    .collect()
+            }
+        };
+
+        for event in sounds {
             match event {
-                AudioEvent::Destroyed => assets.play_destroyed(raudio),
-                AudioEvent::Bounce => assets.play_bounce(raudio),
+                AudioEvent::Destroyed => assets.play(raudio, SoundBank::Destroyed),
+                AudioEvent::Bounce => assets.play(raudio, SoundBank::Bounce),
+                AudioEvent::Attack => assets.play(raudio, SoundBank::Laser),
             }
         }
     }
 
-    pub fn draw(&mut self, rl: &RaylibHandle, d: &RaylibDrawHandle) {
-        d.draw_fps(10, 10);
+    /// Run one step of the game.
+    fn simulate(&mut self) {
+        if let Some(phase) = self
+            .world
+            .get_resource::<VersusState>()
+            .map(|versus| versus.phase)
+        {
+            match phase {
+                RoundPhase::Playing => (),
+                RoundPhase::RoundOver { .. } => {
+                    // Wait before the next round.
+                    if self
+                        .world
+                        .resource_mut::<VersusState>()
+                        .round_over_elapsed()
+                    {
+                        self.next_round();
+                    }
+                    return;
+                }
+                RoundPhase::MatchOver { .. } => return,
+            }
+        }
+
+        if self.is_game_over() {
+            self.game_over_timer += 1;
+        } else {
+            if let Some(stats) = &mut self.stats {
+                stats.step();
+            }
+
+            // Players are despawned as they die, keep their score before it happens.
+            self.score = self
+                .world
+                .query::<&Player>()
+                .iter(&self.world)
+                .map(|player| player.score)
+                .sum();
+        }
+
+        // Bricks may have been added by the stage or its script since the last step.
+        self.stage_clearable |= self.breakable_bricks_left();
+
+        self.schedule.run(&mut self.world);
+        // Let `brick_grid_update` see the bricks destroyed during this step only once.
+        self.world.clear_trackers();
+        let cleared = self.stage_clearable
+            && match self.world.get_resource::<CoopState>() {
+                Some(coop) => coop.stage_cleared,
+                None => self.mode == GameMode::Solo && !self.breakable_bricks_left(),
+            };
+
+        if cleared {
+            let screeninfo = *self.world.resource::<ScreenInfo>();
+            let center = Vector2::new(screeninfo.width, screeninfo.height) / 2.0;
+
+            self.world.send_event(GameplayEvent::new(
+                GameplayEventKind::StageCleared,
+                Cause::LastBrick,
+                center,
+            ));
+        }
+
+        self.handle_events();
+        self.particles.step();
+
+        if cleared {
+            if let Some(mut coop) = self.world.get_resource_mut::<CoopState>() {
+                coop.next_stage();
+            }
+
+            self.level += 1;
+
+            // Rebuild the stage and bring the balls back to their paddles.
+            self.start_stage();
+            self.world.send_event(BallResetEvent { target: None });
+        }
+    }
+
+    pub fn draw(&mut self, d: &RaylibDrawHandle, assets: &Assets) {
+        let theme = assets.theme();
 
         d.clear_background(Color::RAYWHITE);
 
@@ -160,18 +883,27 @@ impl Game {
 
         for brick in self
             .world
-            .query_filtered::<(&Position, &Size, &Colored), With<Brick>>()
+            .query_filtered::<(
+                &Position,
+                &Size,
+                &Colored,
+                &BrickKind,
+                Option<&Detonating>,
+            ), With<Brick>>()
             .iter(&self.world)
         {
-            draw_brick(d, brick);
+            draw_brick(d, theme, self.palette, brick);
         }
 
+        self.particles.draw(d, self.palette);
+
         for player in self
             .world
-            .query_filtered::<(&Position, &Size, &Player, &Colored), With<Player>>()
+            .query_filtered::<(&Position, &Size, &Player, &Colored, Option<&Downed>), With<Player>>(
+            )
             .iter(&self.world)
         {
-            draw_player(d, player, &screeninfo);
+            draw_player(d, theme, self.palette, player);
         }
 
         for ball in self
@@ -179,17 +911,39 @@ impl Game {
             .query_filtered::<(&Position, &Ball, &Colored), With<Ball>>()
             .iter(&self.world)
         {
-            draw_ball(d, ball);
+            draw_ball(d, theme, self.palette, ball);
         }
 
-        // Game over display
-        if self
+        for hazard in self
             .world
-            .query_filtered::<(), With<Player>>()
+            .query::<(&Position, &Hazard, &Colored)>()
             .iter(&self.world)
-            .next()
-            .is_none()
         {
+            draw_hazard(d, self.palette, hazard);
+        }
+
+        self.draw_hud(d, &screeninfo);
+
+        if let Some(versus) = self.world.get_resource::<VersusState>() {
+            draw_round_over(d, versus, &screeninfo);
+        }
+
+        let connecting =
+            matches!(&self.net, Some(NetRole::Client(client)) if !client.is_connected());
+
+        if connecting {
+            let text = "Connexion à l'hôte...";
+
+            d.draw_text(
+                text,
+                (screeninfo.width as i32 - measure_text(text, 36)) / 2,
+                (screeninfo.height as i32) / 2 - 18,
+                36,
+                Color::DARKGRAY,
+            );
+        }
+        // Game over display (versus rounds have their own)
+        else if self.is_game_over() {
             // No more player
             d.draw_rectangle_gradient_ex(
                 Rectangle::new(0.0, 0.0, screeninfo.width, screeninfo.height),
@@ -208,4 +962,246 @@ impl Game {
             );
         }
     }
+
+    fn draw_hud(&mut self, d: &RaylibDrawHandle, screeninfo: &ScreenInfo) {
+        let hud = Hud::new(screeninfo);
+
+        let status = if let Some(versus) = self.world.get_resource::<VersusState>() {
+            Some(versus_status(versus))
+        } else if self.world.contains_resource::<CoopState>() {
+            let team_score: u32 = self
+                .world
+                .query::<&Player>()
+                .iter(&self.world)
+                .map(|player| player.score)
+                .sum();
+
+            Some(coop_status(self.world.resource::<CoopState>(), team_score))
+        } else {
+            None
+        };
+
+        // Network clients don't keep the statistics, nor the time played.
+        let seconds = self.stats.as_ref().map(SessionStats::seconds);
+        let stage = &self.levels[self.level % self.levels.len()].name;
+
+        hud.draw_fps(d);
+        hud.draw_header(d, stage, seconds, status.as_deref());
+
+        // The team lives are in the status in cooperative mode.
+        let show_lives = self.mode != GameMode::Coop;
+
+        for (player, downed) in self
+            .world
+            .query::<(&Player, Has<Downed>)>()
+            .iter(&self.world)
+        {
+            hud.draw_panel(
+                d,
+                self.palette,
+                PlayerPanel {
+                    player,
+                    downed,
+                    show_lives,
+                },
+            );
+        }
+    }
+}
+
+/// Empty cells before the first brick.
+/// Cells a moving brick may slide over: the empty ones next to it, half of
+/// them when another moving brick slides over them too.
+fn free_cells<'a>(cells: impl Iterator<Item = &'a Option<LevelBrick>>) -> f32 {
+    let mut free = 0;
+
+    for cell in cells {
+        match cell {
+            None => free += 1,
+            Some(brick) if brick.kind == BrickKind::Moving => return free as f32 / 2.0,
+            Some(_) => break,
+        }
+    }
+
+    free as f32
+}
+
+fn player_controls(index: usize) -> PlayerControls {
+    match index {
+        0 => PlayerControls {
+            left: KeyboardKey::KEY_LEFT,
+            right: KeyboardKey::KEY_RIGHT,
+            launch: KeyboardKey::KEY_R,
+        },
+        _ => PlayerControls {
+            left: KeyboardKey::KEY_A,
+            right: KeyboardKey::KEY_D,
+            launch: KeyboardKey::KEY_O,
+        },
+    }
+}
+
+fn versus_status(versus: &VersusState) -> String {
+    let wins: Vec<String> = versus.wins.iter().map(u32::to_string).collect();
+
+    format!("Manche {}  -  {}", versus.round, wins.join(" : "))
+}
+
+fn draw_round_over(d: &RaylibDrawHandle, versus: &VersusState, screeninfo: &ScreenInfo) {
+    if let RoundPhase::RoundOver { winner, .. } = versus.phase {
+        let text = match winner {
+            Some(winner) => format!("Joueur {} remporte la manche", winner + 1),
+            None => "Égalité".to_string(),
+        };
+
+        d.draw_text(
+            &text,
+            (screeninfo.width as i32 - measure_text(&text, 36)) / 2,
+            (screeninfo.height as i32) / 2 - 18,
+            36,
+            Color::DARKGRAY,
+        );
+    }
+}
+
+fn coop_status(coop: &CoopState, team_score: u32) -> String {
+    format!(
+        "Niveau {}  -  Équipe : {} points  -  Vies : {}",
+        coop.stage, team_score, coop.lives
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bench::{build_world, Scenario};
+
+    /// Solo game with a computer controlled paddle, built without a window.
+    fn headless_game(bricks: usize, balls: usize) -> (Game, Entity) {
+        let (world, player) = build_world(&Scenario {
+            bricks,
+            balls,
+            ticks: 0,
+        });
+
+        let game = Game {
+            world,
+            ..Game::default()
+        };
+
+        (game, player)
+    }
+
+    /// Run the schedule once, returning the gameplay events sent.
+    fn step(game: &mut Game) -> Vec<GameplayEvent> {
+        game.schedule.run(&mut game.world);
+        game.world.clear_trackers();
+        game.world.resource_mut::<Events<AudioEvent>>().clear();
+
+        game.world
+            .resource_mut::<Events<GameplayEvent>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn losing_the_ball_resets_the_combo() {
+        let (mut game, owner) = headless_game(0, 0);
+        game.world.get_mut::<Player>(owner).unwrap().combo = 5;
+
+        let height = game.world.resource::<ScreenInfo>().height;
+        let mut ball = BallBundle::new(owner, Color::MAROON, &Position(Vector2::zeros()));
+        ball.position = Position(Vector2::new(100.0, height));
+        ball.ball.active = true;
+        game.world.spawn(ball);
+
+        let events = step(&mut game);
+
+        assert!(events
+            .iter()
+            .any(|event| event.kind == GameplayEventKind::BallLost));
+        assert_eq!(game.world.get::<Player>(owner).unwrap().combo, 0);
+    }
+
+    #[test]
+    fn scripts_only_spawn_bricks_in_the_playfield() {
+        let (mut game, _) = headless_game(0, 0);
+        let bricks = |game: &mut Game| game.world.query::<&Brick>().iter(&game.world).count();
+
+        game.apply_script_commands(vec![
+            ScriptCommand::SpawnBrick {
+                column: BRICKS_PER_LINE,
+                row: 0,
+            },
+            ScriptCommand::SpawnBrick {
+                column: 0,
+                row: MAX_BRICK_LINES,
+            },
+        ]);
+        assert_eq!(bricks(&mut game), 0);
+
+        game.apply_script_commands(vec![ScriptCommand::SpawnBrick {
+            column: 0,
+            row: MAX_BRICK_LINES - 1,
+        }]);
+        assert_eq!(bricks(&mut game), 1);
+    }
+
+    #[test]
+    fn moving_bricks_of_a_row_dont_overlap() {
+        let (mut game, _) = headless_game(0, 0);
+        game.brick_size = Vector2::new(10.0, 10.0);
+        game.set_levels(vec![Level::parse("test", "~..~.~#~\n").unwrap()]);
+        game.reset_bricks();
+
+        let mut ranges: Vec<(f32, f32)> = game
+            .world
+            .query::<&Sliding>()
+            .iter(&game.world)
+            .map(|sliding| (sliding.min, sliding.max))
+            .collect();
+        ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        assert_eq!(
+            ranges,
+            [(5.0, 15.0), (25.0, 40.0), (50.0, 55.0), (75.0, 75.0)]
+        );
+
+        // Even at the ends of their ranges, neighbours stay a brick apart.
+        for pair in ranges.windows(2) {
+            assert!(pair[1].0 - pair[0].1 >= game.brick_size.x);
+        }
+    }
+
+    #[test]
+    fn eliminated_players_keep_their_round_score() {
+        let (mut game, owner) = headless_game(1, 0);
+        game.world.get_mut::<Player>(owner).unwrap().score = 2;
+        game.world
+            .insert_resource(VersusState::new(3, WinCondition::LastStanding, 1));
+
+        let screeninfo = *game.world.resource::<ScreenInfo>();
+        let opponents: Vec<Entity> = [(1, 7), (2, 4)]
+            .into_iter()
+            .map(|(index, score)| {
+                let mut bundle = PlayerBundle::new(&screeninfo, index, Color::BLACK, Color::BLUE);
+                bundle.player.score = score;
+                game.world.spawn(bundle).id()
+            })
+            .collect();
+
+        // The first opponent is knocked out while the round goes on.
+        game.world.get_mut::<Player>(opponents[0]).unwrap().life = 0;
+        step(&mut game);
+        assert!(game.world.get_entity(opponents[0]).is_none());
+        assert!(game.world.resource::<VersusState>().phase == RoundPhase::Playing);
+
+        // The second one ends the round, and the match.
+        game.world.get_mut::<Player>(opponents[1]).unwrap().life = 0;
+        step(&mut game);
+
+        let results = game.world.resource::<VersusState>().results().unwrap();
+        assert_eq!(results.winner, Some(0));
+        assert_eq!(results.scores, vec![2, 7, 4]);
+    }
 }
@@ -0,0 +1,98 @@
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use super::protocol::Message;
+
+/// Largest datagram we expect to receive (a snapshot of a full stage fits easily).
+const MAX_DATAGRAM_SIZE: usize = 64 * 1024;
+
+/// Degraded network conditions applied to outgoing packets, to try the game
+/// on loopback as if it was played over a real network.
+#[derive(Clone, Copy, Default)]
+pub struct LinkConditions {
+    /// Delay added before sending each packet.
+    pub latency: Duration,
+
+    /// Probability (between 0 and 1) of dropping a packet.
+    pub loss: f32,
+}
+
+/// Non-blocking UDP socket exchanging `Message`s.
+pub struct Link {
+    socket: UdpSocket,
+    conditions: LinkConditions,
+
+    /// Packets held back to simulate latency, with the time they are due.
+    delayed: Vec<(Instant, SocketAddr, Vec<u8>)>,
+}
+
+impl Link {
+    pub fn bind(addr: SocketAddr, conditions: LinkConditions) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            conditions,
+            delayed: vec![],
+        })
+    }
+
+    pub fn send(&mut self, target: SocketAddr, message: &Message) {
+        if self.conditions.loss > 0.0 && fastrand::f32() < self.conditions.loss {
+            // Lost in the void
+            return;
+        }
+
+        let data = message.encode();
+
+        if self.conditions.latency.is_zero() {
+            self.send_now(target, &data);
+        } else {
+            self.delayed
+                .push((Instant::now() + self.conditions.latency, target, data));
+        }
+    }
+
+    #[cfg(test)]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.socket.local_addr().unwrap()
+    }
+
+    /// Send the delayed packets that are due and read every pending message.
+    pub fn poll(&mut self) -> Vec<(SocketAddr, Message)> {
+        let now = Instant::now();
+
+        let (due, delayed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.delayed)
+            .into_iter()
+            .partition(|(time, ..)| *time <= now);
+
+        self.delayed = delayed;
+
+        for (_, target, data) in due {
+            self.send_now(target, &data);
+        }
+
+        let mut messages = vec![];
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+
+        // Stop on WouldBlock (nothing left to read), or on errors such as an
+        // ICMP port unreachable meaning the peer isn't there (yet).
+        while let Ok((len, source)) = self.socket.recv_from(&mut buffer) {
+            // Ignore anything that isn't from us.
+            if let Some(message) = Message::decode(&buffer[..len]) {
+                messages.push((source, message));
+            }
+        }
+
+        messages
+    }
+
+    fn send_now(&self, target: SocketAddr, data: &[u8]) {
+        // UDP is unreliable anyway, a failed send is just another lost packet.
+        self.socket.send_to(data, target).ok();
+    }
+}
@@ -0,0 +1,531 @@
+//! LAN multiplayer: the host runs the simulation and sends authoritative
+//! snapshots to its clients, which only send the keys of their player.
+
+mod link;
+mod protocol;
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use bevy_ecs::{
+    entity::Entity,
+    query::{Or, With},
+    world::World,
+};
+use nalgebra::Vector2;
+
+pub use self::link::LinkConditions;
+use self::{
+    link::Link,
+    protocol::{BrickState, CircleState, Message, PlayerState, RemoteInput, Snapshot, Status},
+};
+use super::{
+    audio::AudioEvent,
+    components::{Colored, Position, Size},
+    entities::{
+        ball::Ball,
+        brick::{Brick, BrickBundle, BrickKind},
+        hazard::Hazard,
+        player::{Downed, Player},
+    },
+    player_controls,
+    resources::{InputManager, ScreenInfo},
+    rules::{CoopState, GameMode, VersusState, WinCondition},
+};
+
+const DEFAULT_PORT: u16 = 7878;
+
+/// Time without snapshot after which a client asks again to join.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Networking setup requested on the command line.
+#[derive(Clone, Copy)]
+pub enum NetOptions {
+    Host {
+        port: u16,
+        conditions: LinkConditions,
+    },
+    Join {
+        host: SocketAddr,
+        conditions: LinkConditions,
+    },
+}
+
+impl NetOptions {
+    /// Parse `--host [port]` or `--join <address:port>`, optionally followed by
+    /// `--latency <ms>` and `--loss <percent>` to simulate a bad network.
+    /// These are taken out of `args`.
+    pub fn from_args(args: &mut Vec<String>) -> Option<Self> {
+        let mut remaining = vec![];
+        let mut iter = std::mem::take(args).into_iter().peekable();
+        let mut options = None;
+        let mut conditions = LinkConditions::default();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--host" => {
+                    let port = match iter.next_if(|value| !value.starts_with("--")) {
+                        Some(value) => value.parse().ok(),
+                        None => Some(DEFAULT_PORT),
+                    };
+
+                    match port {
+                        Some(port) => options = Some(NetOptions::Host { port, conditions }),
+                        None => eprintln!("--host expects a port, ignored"),
+                    }
+                }
+                "--join" => match iter.next().and_then(|value| value.parse().ok()) {
+                    Some(host) => options = Some(NetOptions::Join { host, conditions }),
+                    None => {
+                        eprintln!("--join expects an address such as 192.168.1.2:7878, ignored")
+                    }
+                },
+                "--latency" => match iter.next().and_then(|value| value.parse().ok()) {
+                    Some(ms) => conditions.latency = Duration::from_millis(ms),
+                    None => eprintln!("--latency expects a number of milliseconds, ignored"),
+                },
+                "--loss" => match iter.next().and_then(|value| value.parse::<f32>().ok()) {
+                    Some(percent) => conditions.loss = percent / 100.0,
+                    None => eprintln!("--loss expects a percentage, ignored"),
+                },
+                _ => remaining.push(arg),
+            }
+        }
+
+        *args = remaining;
+
+        // Conditions may be given after the role.
+        options.map(|options| match options {
+            NetOptions::Host { port, .. } => NetOptions::Host { port, conditions },
+            NetOptions::Join { host, .. } => NetOptions::Join { host, conditions },
+        })
+    }
+}
+
+pub enum NetRole {
+    Host(Host),
+    Client(Client),
+}
+
+struct RemoteClient {
+    addr: SocketAddr,
+    player_index: usize,
+    input: RemoteInput,
+
+    /// Whether launch was held during the previous frame, to detect presses.
+    launch_was_down: bool,
+}
+
+pub struct Host {
+    link: Link,
+    clients: Vec<RemoteClient>,
+    tick: u32,
+}
+
+impl Host {
+    pub fn bind(port: u16, conditions: LinkConditions) -> io::Result<Self> {
+        Ok(Self {
+            link: Link::bind((Ipv4Addr::UNSPECIFIED, port).into(), conditions)?,
+            clients: vec![],
+            tick: 0,
+        })
+    }
+
+    /// Handle the messages of the clients and apply their inputs to their paddles.
+    pub fn receive(&mut self, world: &mut World, player_count: usize) {
+        for (source, message) in self.link.poll() {
+            let known = self.clients.iter().position(|client| client.addr == source);
+
+            match (message, known) {
+                // Welcome may have been lost, send it again.
+                (Message::Hello, Some(i)) => {
+                    let player_index = self.clients[i].player_index as u8;
+                    self.link.send(source, &Message::Welcome { player_index });
+                }
+                // The host plays the first player, clients take the next free ones.
+                (Message::Hello, None) if self.clients.len() + 1 < player_count => {
+                    let player_index = self.clients.len() + 1;
+
+                    self.clients.push(RemoteClient {
+                        addr: source,
+                        player_index,
+                        input: RemoteInput::default(),
+                        launch_was_down: false,
+                    });
+
+                    self.link.send(
+                        source,
+                        &Message::Welcome {
+                            player_index: player_index as u8,
+                        },
+                    );
+                }
+                // Ignore inputs arriving out of order.
+                (Message::Input(input), Some(i)) if input.seq > self.clients[i].input.seq => {
+                    self.clients[i].input = input;
+                }
+                _ => (),
+            }
+        }
+
+        let mut input_manager = world.resource_mut::<InputManager>();
+
+        for client in &mut self.clients {
+            let controls = player_controls(client.player_index);
+            let launch_pressed = client.input.launch && !client.launch_was_down;
+
+            input_manager.set(controls.left, client.input.left, false);
+            input_manager.set(controls.right, client.input.right, false);
+            input_manager.set(controls.launch, client.input.launch, launch_pressed);
+
+            client.launch_was_down = client.input.launch;
+        }
+    }
+
+    /// Send the state of the game to every client.
+    pub fn broadcast(&mut self, world: &mut World, sounds: &[AudioEvent]) {
+        if self.clients.is_empty() {
+            return;
+        }
+
+        self.tick += 1;
+        let snapshot = Message::Snapshot(capture(world, self.tick, sounds));
+
+        for client in &self.clients {
+            self.link.send(client.addr, &snapshot);
+        }
+    }
+}
+
+pub struct Client {
+    link: Link,
+    host: SocketAddr,
+
+    /// Player given by the host, `None` until the host welcomes us.
+    player_index: Option<usize>,
+    seq: u32,
+    last_tick: u32,
+    last_received: Instant,
+}
+
+impl Client {
+    pub fn connect(host: SocketAddr, conditions: LinkConditions) -> io::Result<Self> {
+        let local: SocketAddr = match host {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => "[::]:0".parse().unwrap(),
+        };
+
+        Ok(Self {
+            link: Link::bind(local, conditions)?,
+            host,
+            player_index: None,
+            seq: 0,
+            last_tick: 0,
+            last_received: Instant::now(),
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.player_index.is_some()
+    }
+
+    /// Send the keys of our player, or ask to join if the host doesn't know us (anymore).
+    pub fn send_input(&mut self, left: bool, right: bool, launch: bool) {
+        if self.last_received.elapsed() > CONNECTION_TIMEOUT {
+            // The host may have started a new game.
+            self.player_index = None;
+            self.last_tick = 0;
+        }
+
+        let message = if self.player_index.is_some() {
+            self.seq += 1;
+
+            Message::Input(RemoteInput {
+                seq: self.seq,
+                left,
+                right,
+                launch,
+            })
+        } else {
+            Message::Hello
+        };
+
+        self.link.send(self.host, &message);
+    }
+
+    /// Latest snapshot received since the previous call, carrying the sounds
+    /// of the skipped ones.
+    pub fn receive(&mut self) -> Option<Snapshot> {
+        let mut latest: Option<Snapshot> = None;
+        let mut sounds = vec![];
+
+        for (source, message) in self.link.poll() {
+            if source != self.host {
+                continue;
+            }
+
+            match message {
+                Message::Welcome { player_index } => {
+                    self.player_index = Some(player_index as usize);
+                    self.last_received = Instant::now();
+                }
+                Message::Snapshot(mut snapshot) if snapshot.tick > self.last_tick => {
+                    self.last_tick = snapshot.tick;
+                    self.last_received = Instant::now();
+
+                    sounds.append(&mut snapshot.sounds);
+                    latest = Some(snapshot);
+                }
+                _ => (),
+            }
+        }
+
+        latest.map(|snapshot| Snapshot { sounds, ..snapshot })
+    }
+}
+
+fn capture(world: &mut World, tick: u32, sounds: &[AudioEvent]) -> Snapshot {
+    let screeninfo = *world.resource::<ScreenInfo>();
+
+    let status = if let Some(versus) = world.get_resource::<VersusState>() {
+        Status::Versus {
+            round: versus.round,
+            wins: versus.wins.clone(),
+            phase: versus.phase,
+        }
+    } else if let Some(coop) = world.get_resource::<CoopState>() {
+        Status::Coop {
+            lives: coop.lives,
+            stage: coop.stage,
+        }
+    } else {
+        Status::Solo
+    };
+
+    let mut indices = HashMap::new();
+    let mut players = vec![];
+
+    for (entity, position, size, player, color, downed) in world
+        .query::<(Entity, &Position, &Size, &Player, &Colored, Option<&Downed>)>()
+        .iter(world)
+    {
+        indices.insert(entity, player.index as u8);
+
+        players.push(PlayerState {
+            index: player.index as u8,
+            position: position.0,
+            size: size.0,
+            color: color.0,
+            ball_color: player.ball_color,
+            life: player.life,
+            score: player.score,
+            stunned: player.stunned,
+            combo: player.combo,
+            downed: downed.is_some(),
+        });
+    }
+
+    // Entities of eliminated players are sent with an out of range index.
+    let index_of = |entity| indices.get(&entity).copied().unwrap_or(u8::MAX);
+
+    let balls = world
+        .query::<(&Position, &Ball, &Colored)>()
+        .iter(world)
+        .map(|(position, ball, color)| CircleState {
+            player: index_of(ball.owner),
+            position: position.0,
+            radius: ball.radius as f32,
+            color: color.0,
+        })
+        .collect();
+
+    let hazards = world
+        .query::<(&Position, &Hazard, &Colored)>()
+        .iter(world)
+        .map(|(position, hazard, color)| CircleState {
+            player: index_of(hazard.target),
+            position: position.0,
+            radius: hazard.radius,
+            color: color.0,
+        })
+        .collect();
+
+    let bricks = world
+        .query_filtered::<(&Position, &Size, &Colored, &BrickKind), With<Brick>>()
+        .iter(world)
+        .map(|(position, size, color, kind)| BrickState {
+            position: position.0,
+            size: size.0,
+            color: color.0,
+            kind: kind.to_byte(),
+        })
+        .collect();
+
+    Snapshot {
+        tick,
+        screen: Vector2::new(screeninfo.width, screeninfo.height),
+        status,
+        players,
+        balls,
+        hazards,
+        bricks,
+        sounds: sounds.iter().map(|sound| sound.to_byte()).collect(),
+    }
+}
+
+/// Replace the entities of the world by the ones of the snapshot, returns the mode
+/// played by the host.
+pub fn apply_snapshot(world: &mut World, snapshot: Snapshot) -> GameMode {
+    let screeninfo = *world.resource::<ScreenInfo>();
+
+    // The host may have another resolution.
+    let scale = Vector2::new(
+        screeninfo.width / snapshot.screen.x,
+        screeninfo.height / snapshot.screen.y,
+    );
+
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<Player>, With<Ball>, With<Brick>, With<Hazard>)>>()
+        .iter(world)
+        .collect();
+
+    for entity in entities {
+        world.despawn(entity);
+    }
+
+    let mut players = HashMap::new();
+
+    for player in snapshot.players {
+        let mut entity = world.spawn((
+            Position(player.position.component_mul(&scale)),
+            Size(player.size.component_mul(&scale)),
+            Player {
+                index: player.index as usize,
+                life: player.life,
+                score: player.score,
+                ball_color: player.ball_color,
+                stunned: player.stunned,
+                combo: player.combo,
+            },
+            Colored(player.color),
+        ));
+
+        if player.downed {
+            entity.insert(Downed);
+        }
+
+        players.insert(player.index, entity.id());
+    }
+
+    let entity_of = |index| players.get(&index).copied().unwrap_or(Entity::PLACEHOLDER);
+
+    for ball in snapshot.balls {
+        world.spawn((
+            Position(ball.position.component_mul(&scale)),
+            Ball {
+                radius: (ball.radius * scale.x) as i32,
+                active: true,
+                owner: entity_of(ball.player),
+            },
+            Colored(ball.color),
+        ));
+    }
+
+    for hazard in snapshot.hazards {
+        world.spawn((
+            Position(hazard.position.component_mul(&scale)),
+            Hazard {
+                target: entity_of(hazard.player),
+                radius: hazard.radius * scale.x,
+            },
+            Colored(hazard.color),
+        ));
+    }
+
+    world.spawn_batch(snapshot.bricks.into_iter().map(|brick| BrickBundle {
+        position: Position(brick.position.component_mul(&scale)),
+        size: Size(brick.size.component_mul(&scale)),
+        color: Colored(brick.color),
+        kind: BrickKind::from_byte(brick.kind).unwrap_or_default(),
+        brick: Brick,
+    }));
+
+    match snapshot.status {
+        Status::Solo => GameMode::Solo,
+        Status::Versus { round, wins, phase } => {
+            // Only used for display, the rules are enforced by the host.
+            let mut versus = VersusState::new(wins.len(), WinCondition::LastStanding, u32::MAX);
+            versus.round = round;
+            versus.wins = wins;
+            versus.phase = phase;
+
+            world.insert_resource(versus);
+            GameMode::Versus
+        }
+        Status::Coop { lives, stage } => {
+            let mut coop = CoopState::new(players.len());
+            coop.lives = lives;
+            coop.stage = stage;
+
+            world.insert_resource(coop);
+            GameMode::Coop
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn loopback_over_a_bad_link() {
+        // Packets are dropped at random.
+        fastrand::seed(7);
+
+        let conditions = LinkConditions {
+            latency: Duration::from_millis(5),
+            loss: 0.2,
+        };
+
+        let mut host = Host::bind(0, conditions).unwrap();
+        let port = host.link.local_addr().port();
+        let mut client = Client::connect((Ipv4Addr::LOCALHOST, port).into(), conditions).unwrap();
+
+        let mut world = World::new();
+        world.insert_resource(ScreenInfo {
+            width: 1366.0,
+            height: 768.0,
+        });
+        world.insert_resource(InputManager::default());
+
+        let left = player_controls(1).left;
+        let mut snapshots = 0;
+
+        for _ in 0..500 {
+            client.send_input(true, false, false);
+            host.receive(&mut world, 2);
+            host.broadcast(&mut world, &[AudioEvent::Bounce]);
+
+            if let Some(snapshot) = client.receive() {
+                snapshots += 1;
+                assert!(!snapshot.sounds.is_empty());
+            }
+
+            if snapshots >= 10 && world.resource::<InputManager>().is_key_down(left) {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(client.is_connected());
+        assert!(snapshots >= 10, "only {snapshots} snapshots received");
+        assert!(world.resource::<InputManager>().is_key_down(left));
+    }
+}
@@ -0,0 +1,492 @@
+//! Messages exchanged between the host and its clients.
+//!
+//! Every datagram starts with a magic byte and the protocol version,
+//! followed by the message kind and its payload (little endian).
+
+use nalgebra::Vector2;
+use raylib::ffi::Color;
+
+use crate::game::rules::RoundPhase;
+
+const MAGIC: u8 = b'P';
+const PROTOCOL_VERSION: u8 = 3;
+
+const KIND_HELLO: u8 = 0;
+const KIND_WELCOME: u8 = 1;
+const KIND_INPUT: u8 = 2;
+const KIND_SNAPSHOT: u8 = 3;
+
+pub enum Message {
+    /// Sent by a client until the host welcomes it.
+    Hello,
+    /// Player slot given by the host to a client.
+    Welcome {
+        player_index: u8,
+    },
+    Input(RemoteInput),
+    Snapshot(Snapshot),
+}
+
+/// Keys held by a remote player.
+#[derive(Clone, Copy, Default)]
+pub struct RemoteInput {
+    pub seq: u32,
+    pub left: bool,
+    pub right: bool,
+    pub launch: bool,
+}
+
+/// Mode specific state displayed by the clients.
+pub enum Status {
+    Solo,
+    Versus {
+        round: u32,
+        wins: Vec<u32>,
+        phase: RoundPhase,
+    },
+    Coop {
+        lives: u32,
+        stage: u32,
+    },
+}
+
+pub struct PlayerState {
+    pub index: u8,
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub color: Color,
+    pub ball_color: Color,
+    pub life: i32,
+    pub score: u32,
+    pub stunned: u32,
+    pub combo: u32,
+    pub downed: bool,
+}
+
+/// Ball or hazard, `player` being respectively its owner or its target.
+pub struct CircleState {
+    pub player: u8,
+    pub position: Vector2<f32>,
+    pub radius: f32,
+    pub color: Color,
+}
+
+pub struct BrickState {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub color: Color,
+    /// See `BrickKind::to_byte`.
+    pub kind: u8,
+}
+
+/// Authoritative state of the game at a given tick.
+pub struct Snapshot {
+    pub tick: u32,
+    /// Screen size of the host, used to rescale the positions.
+    pub screen: Vector2<f32>,
+    pub status: Status,
+    pub players: Vec<PlayerState>,
+    pub balls: Vec<CircleState>,
+    pub hazards: Vec<CircleState>,
+    pub bricks: Vec<BrickState>,
+    /// Audio events (see `AudioEvent::to_byte`) emitted during this tick.
+    pub sounds: Vec<u8>,
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer(vec![MAGIC, PROTOCOL_VERSION]);
+
+        match self {
+            Message::Hello => w.u8(KIND_HELLO),
+            Message::Welcome { player_index } => {
+                w.u8(KIND_WELCOME);
+                w.u8(*player_index);
+            }
+            Message::Input(input) => {
+                w.u8(KIND_INPUT);
+                w.u32(input.seq);
+                w.bool(input.left);
+                w.bool(input.right);
+                w.bool(input.launch);
+            }
+            Message::Snapshot(snapshot) => {
+                w.u8(KIND_SNAPSHOT);
+                snapshot.encode(&mut w);
+            }
+        }
+
+        w.0
+    }
+
+    /// Decode a datagram, `None` if it is malformed or from another protocol version.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut r = Reader(data);
+
+        if r.u8()? != MAGIC || r.u8()? != PROTOCOL_VERSION {
+            return None;
+        }
+
+        let message = match r.u8()? {
+            KIND_HELLO => Message::Hello,
+            KIND_WELCOME => Message::Welcome {
+                player_index: r.u8()?,
+            },
+            KIND_INPUT => Message::Input(RemoteInput {
+                seq: r.u32()?,
+                left: r.bool()?,
+                right: r.bool()?,
+                launch: r.bool()?,
+            }),
+            KIND_SNAPSHOT => Message::Snapshot(Snapshot::decode(&mut r)?),
+            _ => return None,
+        };
+
+        // Trailing garbage
+        if !r.0.is_empty() {
+            return None;
+        }
+
+        Some(message)
+    }
+}
+
+impl Snapshot {
+    fn encode(&self, w: &mut Writer) {
+        w.u32(self.tick);
+        w.vector(self.screen);
+
+        match &self.status {
+            Status::Solo => w.u8(0),
+            Status::Versus { round, wins, phase } => {
+                w.u8(1);
+                w.u32(*round);
+                w.len(wins.len());
+                wins.iter().for_each(|wins| w.u32(*wins));
+
+                match phase {
+                    RoundPhase::Playing => w.u8(0),
+                    RoundPhase::RoundOver { winner, timer } => {
+                        w.u8(1);
+                        w.winner(*winner);
+                        w.u32(*timer);
+                    }
+                    RoundPhase::MatchOver { winner } => {
+                        w.u8(2);
+                        w.winner(*winner);
+                    }
+                }
+            }
+            Status::Coop { lives, stage } => {
+                w.u8(2);
+                w.u32(*lives);
+                w.u32(*stage);
+            }
+        }
+
+        w.len(self.players.len());
+        for player in &self.players {
+            w.u8(player.index);
+            w.vector(player.position);
+            w.vector(player.size);
+            w.color(player.color);
+            w.color(player.ball_color);
+            w.u32(player.life as u32);
+            w.u32(player.score);
+            w.u32(player.stunned);
+            w.u32(player.combo);
+            w.bool(player.downed);
+        }
+
+        for circles in [&self.balls, &self.hazards] {
+            w.len(circles.len());
+            for circle in circles {
+                w.u8(circle.player);
+                w.vector(circle.position);
+                w.f32(circle.radius);
+                w.color(circle.color);
+            }
+        }
+
+        w.len(self.bricks.len());
+        for brick in &self.bricks {
+            w.vector(brick.position);
+            w.vector(brick.size);
+            w.color(brick.color);
+            w.u8(brick.kind);
+        }
+
+        w.len(self.sounds.len());
+        self.sounds.iter().for_each(|sound| w.u8(*sound));
+    }
+
+    fn decode(r: &mut Reader) -> Option<Self> {
+        let tick = r.u32()?;
+        let screen = r.vector()?;
+
+        let status = match r.u8()? {
+            0 => Status::Solo,
+            1 => Status::Versus {
+                round: r.u32()?,
+                wins: (0..r.len()?).map(|_| r.u32()).collect::<Option<_>>()?,
+                phase: match r.u8()? {
+                    0 => RoundPhase::Playing,
+                    1 => RoundPhase::RoundOver {
+                        winner: r.winner()?,
+                        timer: r.u32()?,
+                    },
+                    2 => RoundPhase::MatchOver {
+                        winner: r.winner()?,
+                    },
+                    _ => return None,
+                },
+            },
+            2 => Status::Coop {
+                lives: r.u32()?,
+                stage: r.u32()?,
+            },
+            _ => return None,
+        };
+
+        let players = (0..r.len()?)
+            .map(|_| {
+                Some(PlayerState {
+                    index: r.u8()?,
+                    position: r.vector()?,
+                    size: r.vector()?,
+                    color: r.color()?,
+                    ball_color: r.color()?,
+                    life: r.u32()? as i32,
+                    score: r.u32()?,
+                    stunned: r.u32()?,
+                    combo: r.u32()?,
+                    downed: r.bool()?,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        let mut read_circles = || {
+            (0..r.len()?)
+                .map(|_| {
+                    Some(CircleState {
+                        player: r.u8()?,
+                        position: r.vector()?,
+                        radius: r.f32()?,
+                        color: r.color()?,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+
+        let balls = read_circles()?;
+        let hazards = read_circles()?;
+
+        let bricks = (0..r.len()?)
+            .map(|_| {
+                Some(BrickState {
+                    position: r.vector()?,
+                    size: r.vector()?,
+                    color: r.color()?,
+                    kind: r.u8()?,
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        let sounds = (0..r.len()?).map(|_| r.u8()).collect::<Option<_>>()?;
+
+        Some(Self {
+            tick,
+            screen,
+            status,
+            players,
+            balls,
+            hazards,
+            bricks,
+            sounds,
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(len as u32);
+    }
+
+    fn vector(&mut self, value: Vector2<f32>) {
+        self.f32(value.x);
+        self.f32(value.y);
+    }
+
+    fn color(&mut self, color: Color) {
+        self.0
+            .extend_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+
+    fn winner(&mut self, winner: Option<usize>) {
+        self.u8(winner.map(|winner| winner as u8).unwrap_or(u8::MAX));
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.0.get(..N)?.try_into().ok()?;
+        self.0 = &self.0[N..];
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes::<1>().map(|[value]| value)
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        self.u8().map(|value| value != 0)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        let len = self.u32()? as usize;
+
+        // Each element takes at least one byte, reject lengths that can't fit in the datagram.
+        (len <= self.0.len()).then_some(len)
+    }
+
+    fn vector(&mut self) -> Option<Vector2<f32>> {
+        Some(Vector2::new(self.f32()?, self.f32()?))
+    }
+
+    fn color(&mut self) -> Option<Color> {
+        let [r, g, b, a] = self.bytes()?;
+        Some(Color { r, g, b, a })
+    }
+
+    fn winner(&mut self) -> Option<Option<usize>> {
+        self.u8()
+            .map(|winner| (winner != u8::MAX).then_some(winner as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            tick: 42,
+            screen: Vector2::new(1366.0, 768.0),
+            status: Status::Versus {
+                round: 2,
+                wins: vec![1, 0],
+                phase: RoundPhase::RoundOver {
+                    winner: Some(0),
+                    timer: 12,
+                },
+            },
+            players: vec![PlayerState {
+                index: 1,
+                position: Vector2::new(300.0, 700.0),
+                size: Vector2::new(136.0, 20.0),
+                color: Color::BLACK,
+                ball_color: Color::MAROON,
+                life: 3,
+                score: 120,
+                stunned: 4,
+                combo: 9,
+                downed: true,
+            }],
+            balls: vec![CircleState {
+                player: 1,
+                position: Vector2::new(310.5, 640.25),
+                radius: 7.0,
+                color: Color::MAROON,
+            }],
+            hazards: vec![],
+            bricks: vec![BrickState {
+                position: Vector2::new(34.0, 50.0),
+                size: Vector2::new(68.0, 40.0),
+                color: Color::GRAY,
+                kind: 2,
+            }],
+            sounds: vec![0, 3],
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let data = Message::Snapshot(snapshot()).encode();
+
+        let Some(Message::Snapshot(decoded)) = Message::decode(&data) else {
+            panic!("snapshot not decoded");
+        };
+
+        assert_eq!(decoded.tick, 42);
+        assert_eq!(decoded.players[0].combo, 9);
+        assert!(decoded.players[0].downed);
+        assert!(matches!(
+            decoded.status,
+            Status::Versus {
+                round: 2,
+                phase: RoundPhase::RoundOver {
+                    winner: Some(0),
+                    timer: 12
+                },
+                ..
+            }
+        ));
+        assert_eq!(Message::Snapshot(decoded).encode(), data);
+    }
+
+    #[test]
+    fn input_round_trip() {
+        let data = Message::Input(RemoteInput {
+            seq: 7,
+            left: true,
+            right: false,
+            launch: true,
+        })
+        .encode();
+
+        let Some(Message::Input(input)) = Message::decode(&data) else {
+            panic!("input not decoded");
+        };
+
+        assert_eq!(input.seq, 7);
+        assert!(input.left && !input.right && input.launch);
+    }
+
+    #[test]
+    fn reject_other_versions_and_truncated_data() {
+        let mut data = Message::Snapshot(snapshot()).encode();
+
+        assert!(Message::decode(&data[..data.len() - 1]).is_none());
+
+        data[1] = PROTOCOL_VERSION - 1;
+        assert!(Message::decode(&data).is_none());
+    }
+}
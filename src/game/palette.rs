@@ -0,0 +1,182 @@
+//! Palettes the colours of the game are drawn with.
+//!
+//! Entities keep the colours of the standard palette, which are only replaced
+//! when drawn, so saves and network games don't depend on the palette.
+
+use raylib::ffi::Color;
+
+use super::level::BRICK_COLORS;
+
+/// Paddle and ball colours of each player in the standard palette.
+pub const PLAYER_COLORS: [(Color, Color); 4] = [
+    (translucent(Color::BLACK), Color::MAROON),
+    (translucent(Color::BLUE), Color::BLUEVIOLET),
+    (translucent(Color::DARKGREEN), Color::LIME),
+    (translucent(Color::DARKPURPLE), Color::VIOLET),
+];
+
+/// Every other brick of the first colour, making a checkerboard.
+pub const CHECKER_COLOR: Color = Color::LIGHTGRAY;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Red and green told apart by their brightness (green-blind).
+    Deuteranopia,
+    /// Like `Deuteranopia`, with brighter reds (red-blind).
+    Protanopia,
+    /// Without blue against yellow (blue-blind).
+    Tritanopia,
+    /// Saturated colours, with the bricks and paddles outlined.
+    HighContrast,
+}
+
+/// Colours of a palette, in the same order for every one.
+struct Colors {
+    bricks: [Color; BRICK_COLORS.len()],
+    checker: Color,
+    players: [(Color, Color); PLAYER_COLORS.len()],
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b, a: 255 }
+}
+
+/// Same as `color.fade(0.5)`, usable in constants.
+const fn translucent(color: Color) -> Color {
+    Color { a: 127, ..color }
+}
+
+impl Colors {
+    fn iter(&self) -> impl Iterator<Item = Color> + '_ {
+        self.bricks.iter().copied().chain([self.checker]).chain(
+            self.players
+                .iter()
+                .flat_map(|(paddle, ball)| [*paddle, *ball]),
+        )
+    }
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deutéranopie",
+            Palette::Protanopia => "Protanopie",
+            Palette::Tritanopia => "Tritanopie",
+            Palette::HighContrast => "Contraste élevé",
+        }
+    }
+
+    fn colors(self) -> Colors {
+        match self {
+            Palette::Standard => Colors {
+                bricks: BRICK_COLORS,
+                checker: CHECKER_COLOR,
+                players: PLAYER_COLORS,
+            },
+            // Okabe-Ito colours, which stay distinct without red or green cones.
+            Palette::Deuteranopia | Palette::Protanopia => {
+                let vermillion = match self {
+                    Palette::Protanopia => rgb(240, 120, 40),
+                    _ => rgb(213, 94, 0),
+                };
+
+                Colors {
+                    bricks: [
+                        rgb(130, 130, 130),
+                        vermillion,
+                        rgb(230, 159, 0),
+                        rgb(240, 228, 66),
+                        rgb(0, 158, 115),
+                        rgb(86, 180, 233),
+                        rgb(0, 114, 178),
+                        rgb(204, 121, 167),
+                        rgb(255, 200, 225),
+                        rgb(90, 70, 50),
+                    ],
+                    checker: rgb(200, 200, 200),
+                    players: [
+                        (translucent(Color::BLACK), vermillion),
+                        (translucent(rgb(0, 114, 178)), rgb(86, 180, 233)),
+                        (translucent(rgb(0, 158, 115)), rgb(0, 158, 115)),
+                        (translucent(rgb(204, 121, 167)), rgb(204, 121, 167)),
+                    ],
+                }
+            }
+            Palette::Tritanopia => Colors {
+                bricks: [
+                    rgb(130, 130, 130),
+                    rgb(220, 40, 40),
+                    rgb(255, 130, 120),
+                    rgb(255, 190, 200),
+                    rgb(0, 150, 150),
+                    rgb(120, 210, 210),
+                    rgb(0, 90, 100),
+                    rgb(160, 0, 90),
+                    rgb(240, 100, 170),
+                    rgb(90, 40, 40),
+                ],
+                checker: rgb(200, 200, 200),
+                players: [
+                    (translucent(Color::BLACK), rgb(200, 0, 0)),
+                    (translucent(rgb(0, 128, 128)), rgb(0, 170, 170)),
+                    (translucent(rgb(90, 40, 40)), rgb(160, 0, 90)),
+                    (translucent(rgb(120, 120, 120)), rgb(240, 100, 170)),
+                ],
+            },
+            Palette::HighContrast => Colors {
+                bricks: [
+                    rgb(90, 90, 90),
+                    rgb(255, 0, 0),
+                    rgb(255, 128, 0),
+                    rgb(255, 220, 0),
+                    rgb(0, 200, 0),
+                    rgb(0, 220, 255),
+                    rgb(0, 0, 255),
+                    rgb(160, 0, 255),
+                    rgb(255, 0, 200),
+                    rgb(120, 60, 0),
+                ],
+                checker: rgb(220, 220, 220),
+                players: [
+                    (Color::BLACK, rgb(255, 0, 0)),
+                    (rgb(0, 0, 200), rgb(0, 0, 255)),
+                    (rgb(0, 120, 0), rgb(0, 200, 0)),
+                    (rgb(120, 0, 160), rgb(160, 0, 255)),
+                ],
+            },
+        }
+    }
+
+    /// Colour to draw in place of a colour of the standard palette. Other
+    /// colours are drawn as they are.
+    pub fn resolve(self, color: Color) -> Color {
+        if self == Palette::Standard {
+            return color;
+        }
+
+        let key = |color: Color| (color.r, color.g, color.b, color.a);
+
+        Palette::Standard
+            .colors()
+            .iter()
+            .zip(self.colors().iter())
+            .find(|(standard, _)| key(*standard) == key(color))
+            .map_or(color, |(_, replacement)| replacement)
+    }
+
+    /// Whether bricks and paddles are outlined.
+    pub fn outlines(self) -> bool {
+        self == Palette::HighContrast
+    }
+}
@@ -0,0 +1,135 @@
+//! Debris flying off the destroyed bricks, and the points they scored rising
+//! from where they were.
+//!
+//! They are only drawn, take no part in the game and aren't sent over the
+//! network, so they are kept out of the world.
+
+use std::f32::consts::TAU;
+
+use fastrand::Rng;
+use nalgebra::Vector2;
+use raylib::{
+    core::{
+        drawing::{RaylibDraw, RaylibDrawHandle},
+        text::measure_text,
+    },
+    ffi::Color,
+};
+
+use super::palette::Palette;
+
+const DEBRIS_PER_BRICK: usize = 8;
+
+/// Steps a piece of debris lasts.
+const DEBRIS_LIFETIME: u32 = 30;
+
+const DEBRIS_SIZE: i32 = 4;
+const GRAVITY: f32 = 0.3;
+
+/// Steps the points of a brick stay on screen.
+const POPUP_LIFETIME: u32 = 45;
+const POPUP_SPEED: f32 = 1.0;
+
+struct Debris {
+    position: Vector2<f32>,
+    velocity: Vector2<f32>,
+    color: Color,
+    age: u32,
+}
+
+struct Popup {
+    position: Vector2<f32>,
+    points: u32,
+    color: Color,
+    age: u32,
+}
+
+pub struct Particles {
+    debris: Vec<Debris>,
+    popups: Vec<Popup>,
+    /// Own generator, so that the debris don't change the random numbers of the game.
+    rng: Rng,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Self {
+            debris: vec![],
+            popups: vec![],
+            rng: Rng::with_seed(0),
+        }
+    }
+}
+
+impl Particles {
+    /// Throw debris in every direction from `position`.
+    pub fn burst(&mut self, position: Vector2<f32>, color: Color) {
+        for _ in 0..DEBRIS_PER_BRICK {
+            let angle = self.rng.f32() * TAU;
+            let speed = 1.0 + self.rng.f32() * 3.0;
+
+            self.debris.push(Debris {
+                position,
+                velocity: Vector2::new(angle.cos(), angle.sin()) * speed,
+                color,
+                age: 0,
+            });
+        }
+    }
+
+    /// Show the points scored at `position`.
+    pub fn popup(&mut self, position: Vector2<f32>, points: u32, color: Color) {
+        self.popups.push(Popup {
+            position,
+            points,
+            color,
+            age: 0,
+        });
+    }
+
+    pub fn step(&mut self) {
+        for debris in &mut self.debris {
+            debris.velocity.y += GRAVITY;
+            debris.position += debris.velocity;
+            debris.age += 1;
+        }
+
+        self.debris.retain(|debris| debris.age < DEBRIS_LIFETIME);
+
+        for popup in &mut self.popups {
+            popup.position.y -= POPUP_SPEED;
+            popup.age += 1;
+        }
+
+        self.popups.retain(|popup| popup.age < POPUP_LIFETIME);
+    }
+
+    pub fn draw(&self, d: &RaylibDrawHandle, palette: Palette) {
+        for debris in &self.debris {
+            let alpha = 1.0 - debris.age as f32 / DEBRIS_LIFETIME as f32;
+
+            d.draw_rectangle(
+                debris.position.x as i32,
+                debris.position.y as i32,
+                DEBRIS_SIZE,
+                DEBRIS_SIZE,
+                palette.resolve(debris.color).fade(alpha),
+            );
+        }
+
+        for popup in &self.popups {
+            let alpha = 1.0 - popup.age as f32 / POPUP_LIFETIME as f32;
+            let text = format!("+{}", popup.points);
+            // The higher the multiplier, the bigger the points.
+            let size = (16 + 4 * popup.points as i32).min(40);
+
+            d.draw_text(
+                &text,
+                popup.position.x as i32 - measure_text(&text, size) / 2,
+                popup.position.y as i32 - size / 2,
+                size,
+                palette.resolve(popup.color).fade(alpha),
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use bevy_ecs::system::Resource;
+use bevy_ecs::{entity::Entity, system::Resource};
+use nalgebra::Vector2;
 use raylib::{core::RaylibHandle, ffi::KeyboardKey};
 
 #[derive(Resource, Clone, Copy)]
@@ -13,7 +14,6 @@ pub struct ScreenInfo {
 pub struct KeyState {
     pub pressed: bool,
     pub down: bool,
-    pub up: bool,
 }
 
 #[derive(Default, Resource)]
@@ -25,7 +25,6 @@ impl InputManager {
             *value = KeyState {
                 pressed: rl.is_key_pressed(*key),
                 down: rl.is_key_down(*key),
-                up: rl.is_key_pressed(*key),
             }
         })
     }
@@ -34,8 +33,9 @@ impl InputManager {
         self.0.insert(key, KeyState::default());
     }
 
-    pub fn untrack(&mut self, key: KeyboardKey) {
-        self.0.remove(&key);
+    /// Override the state of a key, used to apply the keys of remote players.
+    pub fn set(&mut self, key: KeyboardKey, down: bool, pressed: bool) {
+        self.0.insert(key, KeyState { pressed, down });
     }
 
     pub fn is_key_down(&self, key: KeyboardKey) -> bool {
@@ -48,8 +48,62 @@ impl InputManager {
             .map(|state| state.pressed)
             .unwrap_or_default()
     }
+}
+
+/// Broad phase of the ball/brick collisions: bricks that don't move, by cell of
+/// a uniform grid. Each brick is stored in the cell of its center, bricks being
+/// at most as large as a cell.
+#[derive(Resource)]
+pub struct BrickGrid {
+    cell_size: Vector2<f32>,
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+
+    /// Cell of each brick.
+    bricks: HashMap<Entity, (i32, i32)>,
+}
+
+impl BrickGrid {
+    pub fn new(cell_size: Vector2<f32>) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            bricks: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: Vector2<f32>) -> (i32, i32) {
+        (
+            (position.x / self.cell_size.x).floor() as i32,
+            (position.y / self.cell_size.y).floor() as i32,
+        )
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vector2<f32>) {
+        let cell = self.cell(position);
+
+        self.remove(entity);
+        self.cells.entry(cell).or_default().push(entity);
+        self.bricks.insert(entity, cell);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some(cell) = self.bricks.remove(&entity) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+            }
+        }
+    }
+
+    /// Bricks which may overlap the box going from `min` to `max`.
+    pub fn query(&self, min: Vector2<f32>, max: Vector2<f32>) -> impl Iterator<Item = Entity> + '_ {
+        // A brick overlapping the box has its center at most half a cell away.
+        let (min_x, min_y) = self.cell(min - self.cell_size / 2.0);
+        let (max_x, max_y) = self.cell(max + self.cell_size / 2.0);
 
-    pub fn is_key_up(&self, key: KeyboardKey) -> bool {
-        self.0.get(&key).map(|state| state.up).unwrap_or_default()
+        (min_y..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
     }
 }
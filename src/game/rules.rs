@@ -0,0 +1,546 @@
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, EventReader, EventWriter},
+    query::{Has, With},
+    system::{Commands, Query, Res, ResMut, Resource},
+};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ai::AiController,
+    audio::AudioEvent,
+    collisions::BALL_SPEED,
+    components::Position,
+    entities::{
+        ball::BallBundle,
+        brick::BrickKind,
+        hazard::HazardBundle,
+        player::{Downed, Player, PlayerControls, PLAYER_MAX_LIFE, PLAYER_SPEED},
+    },
+    events::{Cause, GameplayEvent, GameplayEventKind},
+    resources::InputManager,
+};
+
+/// Points a player has to score to send a hazard to its opponent.
+const ATTACK_COST: u32 = 8;
+
+/// Frames the round result stays on screen before the next round starts.
+pub const ROUND_OVER_DELAY: u32 = 180;
+
+/// Points to reach with `WinCondition::Score` picked from the menu.
+const VERSUS_TARGET_SCORE: u32 = 30;
+
+/// Lives shared by the team at the start of a cooperative game.
+const COOP_SHARED_LIVES: u32 = 8;
+
+/// Lives given back to the team by a continue.
+const COOP_CONTINUE_LIVES: u32 = 3;
+
+/// Width of the paddles of the normal difficulty, relative to the screen.
+const PADDLE_WIDTH: f32 = 0.1;
+
+/// Gameplay constants, set by the difficulty, which mods may tweak.
+#[derive(Resource, Clone, Copy)]
+pub struct Tuning {
+    pub ball_speed: f32,
+    pub player_speed: f32,
+    pub player_lives: i32,
+
+    /// Width of the paddles, relative to the screen.
+    pub paddle_width: f32,
+    /// Factor applied to the speed of a ball each time it hits a paddle.
+    pub ball_acceleration: f32,
+    /// Speed balls stop accelerating at.
+    pub max_ball_speed: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            ball_speed: BALL_SPEED,
+            player_speed: PLAYER_SPEED,
+            player_lives: PLAYER_MAX_LIFE,
+            paddle_width: PADDLE_WIDTH,
+            ball_acceleration: 1.0,
+            max_ball_speed: BALL_SPEED,
+        }
+    }
+}
+
+/// Preset of the gameplay constants, picked from the menu.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Starts like `Normal`, but balls keep getting faster.
+    Arcade,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Arcade,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Facile",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Difficile",
+            Difficulty::Arcade => "Arcade",
+        }
+    }
+
+    /// Name written in the high scores file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Arcade => "arcade",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.id() == id)
+    }
+
+    pub fn tuning(self) -> Tuning {
+        let normal = Tuning::default();
+
+        match self {
+            Difficulty::Easy => Tuning {
+                ball_speed: 8.0,
+                player_speed: 12.0,
+                player_lives: 7,
+                paddle_width: 0.14,
+                max_ball_speed: 8.0,
+                ..normal
+            },
+            Difficulty::Normal => normal,
+            Difficulty::Hard => Tuning {
+                ball_speed: 12.0,
+                player_speed: 11.0,
+                player_lives: 3,
+                paddle_width: 0.08,
+                ball_acceleration: 1.02,
+                max_ball_speed: 16.0,
+            },
+            Difficulty::Arcade => Tuning {
+                player_speed: 12.0,
+                player_lives: 3,
+                ball_acceleration: 1.05,
+                max_ball_speed: 22.0,
+                ..normal
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Solo,
+    Versus,
+    Coop,
+}
+
+impl GameMode {
+    /// Name written in the statistics file.
+    pub fn id(self) -> &'static str {
+        match self {
+            GameMode::Solo => "solo",
+            GameMode::Versus => "versus",
+            GameMode::Coop => "coop",
+        }
+    }
+
+    pub fn player_count(self) -> usize {
+        match self {
+            GameMode::Solo => 1,
+            GameMode::Versus | GameMode::Coop => 2,
+        }
+    }
+}
+
+/// How a versus round is won.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WinCondition {
+    /// First player to reach this score wins the round.
+    Score(u32),
+    /// Once every brick is destroyed, the player with the most lives wins the round.
+    Lives,
+    /// Last paddle standing wins the round.
+    LastStanding,
+}
+
+impl WinCondition {
+    pub const ALL: [WinCondition; 3] = [
+        WinCondition::LastStanding,
+        WinCondition::Score(VERSUS_TARGET_SCORE),
+        WinCondition::Lives,
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            WinCondition::Score(target) => format!("Premier à {target} points"),
+            WinCondition::Lives => "Le plus de vies".to_string(),
+            WinCondition::LastStanding => "Dernier en jeu".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RoundPhase {
+    Playing,
+    /// Round is finished, the next one starts when `timer` reaches 0.
+    RoundOver {
+        winner: Option<usize>,
+        timer: u32,
+    },
+    MatchOver {
+        winner: Option<usize>,
+    },
+}
+
+/// State of a versus match, only present in versus mode.
+#[derive(Resource)]
+pub struct VersusState {
+    pub condition: WinCondition,
+    pub rounds_to_win: u32,
+
+    /// Current round (starting at 1)
+    pub round: u32,
+    pub phase: RoundPhase,
+
+    /// Rounds won by each player.
+    pub wins: Vec<u32>,
+
+    /// Score accumulated by each player over the finished rounds.
+    pub total_scores: Vec<u32>,
+
+    /// Hazards sent by each player during this round.
+    pub attacks_sent: Vec<u32>,
+}
+
+/// Outcome of a finished match, displayed by the results screen.
+pub struct MatchResults {
+    pub winner: Option<usize>,
+    pub wins: Vec<u32>,
+    pub scores: Vec<u32>,
+}
+
+impl VersusState {
+    pub fn new(player_count: usize, condition: WinCondition, rounds_to_win: u32) -> Self {
+        Self {
+            condition,
+            rounds_to_win,
+            round: 1,
+            phase: RoundPhase::Playing,
+            wins: vec![0; player_count],
+            total_scores: vec![0; player_count],
+            attacks_sent: vec![0; player_count],
+        }
+    }
+
+    /// Prepare the state for the next round.
+    pub fn next_round(&mut self) {
+        self.round += 1;
+        self.phase = RoundPhase::Playing;
+        self.attacks_sent.iter_mut().for_each(|sent| *sent = 0);
+    }
+
+    /// Count down the delay between two rounds, returns true once the next round has to start.
+    pub fn round_over_elapsed(&mut self) -> bool {
+        match &mut self.phase {
+            RoundPhase::RoundOver { timer: 0, .. } => true,
+            RoundPhase::RoundOver { timer, .. } => {
+                *timer -= 1;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    pub fn results(&self) -> Option<MatchResults> {
+        match self.phase {
+            RoundPhase::MatchOver { winner } => Some(MatchResults {
+                winner,
+                wins: self.wins.clone(),
+                scores: self.total_scores.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn end_round(&mut self, winner: Option<usize>) {
+        if let Some(winner) = winner {
+            self.wins[winner] += 1;
+
+            if self.wins[winner] >= self.rounds_to_win {
+                self.phase = RoundPhase::MatchOver {
+                    winner: Some(winner),
+                };
+                return;
+            }
+        }
+
+        self.phase = RoundPhase::RoundOver {
+            winner,
+            timer: ROUND_OVER_DELAY,
+        };
+    }
+}
+
+/// State of a cooperative game, only present in cooperative mode.
+#[derive(Resource)]
+pub struct CoopState {
+    /// Lives shared by the whole team.
+    pub lives: u32,
+
+    /// Current stage (starting at 1)
+    pub stage: u32,
+
+    /// Set once every brick of the stage is destroyed.
+    pub stage_cleared: bool,
+
+    /// Whether each player already received its initial ball.
+    pub started: Vec<bool>,
+}
+
+impl CoopState {
+    pub fn new(player_count: usize) -> Self {
+        Self {
+            lives: COOP_SHARED_LIVES,
+            stage: 1,
+            stage_cleared: false,
+            started: vec![false; player_count],
+        }
+    }
+
+    /// Move to the next stage, the team earns a life for clearing the previous one.
+    pub fn next_stage(&mut self) {
+        self.stage += 1;
+        self.lives += 1;
+        self.stage_cleared = false;
+    }
+
+    /// Refill the shared lives after a GAME OVER.
+    pub fn continue_game(&mut self) {
+        self.lives += COOP_CONTINUE_LIVES;
+    }
+}
+
+/// Player with the strictly highest `key`, `None` on ties.
+fn best_player<'a>(players: &[&'a Player], key: impl Fn(&Player) -> i64) -> Option<&'a Player> {
+    let best = players.iter().copied().map(&key).max()?;
+    let mut leaders = players
+        .iter()
+        .copied()
+        .filter(|&player| key(player) == best);
+
+    match (leaders.next(), leaders.next()) {
+        (Some(leader), None) => Some(leader),
+        _ => None,
+    }
+}
+
+pub fn versus_round_logic(
+    mut versus: ResMut<VersusState>,
+    player_query: Query<(&Player, &Position)>,
+    brick_query: Query<&BrickKind>,
+    mut event_writer: EventWriter<GameplayEvent>,
+) {
+    if versus.phase != RoundPhase::Playing {
+        return;
+    }
+
+    // Players without lives are being eliminated, their score is already counted.
+    let players: Vec<&Player> = player_query
+        .iter()
+        .map(|(player, _)| player)
+        .filter(|player| player.life > 0)
+        .collect();
+    let no_bricks = !brick_query.iter().any(|kind| kind.is_breakable());
+
+    let winner = match versus.condition {
+        WinCondition::Score(target) => {
+            // Players reaching the target on the same step: the best one wins, a tie is a draw.
+            let reached: Vec<&Player> = players
+                .iter()
+                .copied()
+                .filter(|player| player.score >= target)
+                .collect();
+
+            (!reached.is_empty()).then(|| {
+                best_player(&reached, |player| player.score as i64).map(|player| player.index)
+            })
+        }
+        WinCondition::Lives | WinCondition::LastStanding => None,
+    };
+
+    let winner = winner.or_else(|| match players.len() {
+        0 => Some(None),
+        1 => Some(Some(players[0].index)),
+        _ if no_bricks => Some(
+            match versus.condition {
+                WinCondition::Lives => best_player(&players, |player| player.life as i64),
+                _ => best_player(&players, |player| player.score as i64),
+            }
+            .map(|player| player.index),
+        ),
+        _ => None,
+    });
+
+    if let Some(winner) = winner {
+        // Keep track of the round scores before the players get reset.
+        for player in &players {
+            versus.total_scores[player.index] += player.score;
+        }
+
+        versus.end_round(winner);
+    }
+
+    if let RoundPhase::MatchOver {
+        winner: Some(winner),
+    } = versus.phase
+    {
+        let position = player_query
+            .iter()
+            .find(|(player, _)| player.index == winner)
+            .map_or(Vector2::zeros(), |(_, position)| position.0);
+
+        event_writer.send(
+            GameplayEvent::new(GameplayEventKind::MatchWon, Cause::Rounds, position).player(winner),
+        );
+    }
+}
+
+/// Sent when a player scores points for a destroyed brick.
+#[derive(Event)]
+pub struct ScoreEvent {
+    pub player: usize,
+    pub points: u32,
+    /// Where the brick was.
+    pub position: Vector2<f32>,
+}
+
+/// Score the bricks destroyed by the players, times the multiplier of their
+/// combo. A combo ends when a ball of the player touches its paddle or is lost.
+pub fn brick_scoring(
+    mut event_reader: EventReader<GameplayEvent>,
+    mut player_query: Query<&mut Player>,
+    mut score_writer: EventWriter<ScoreEvent>,
+) {
+    for event in event_reader.read() {
+        let Some(index) = event.player else {
+            continue;
+        };
+
+        for mut player in player_query
+            .iter_mut()
+            .filter(|player| player.index == index)
+        {
+            match event.kind {
+                GameplayEventKind::BrickDestroyed => {
+                    let points = player.multiplier();
+
+                    player.score += points;
+                    player.combo += 1;
+
+                    score_writer.send(ScoreEvent {
+                        player: index,
+                        points,
+                        position: event.position,
+                    });
+                }
+                GameplayEventKind::PaddleHit | GameplayEventKind::BallLost => player.combo = 0,
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Send a hazard to the opponent each time a player scores `ATTACK_COST` points.
+pub fn versus_attacks(
+    mut versus: ResMut<VersusState>,
+    player_query: Query<(Entity, &Player, &Position)>,
+    mut audio_writer: EventWriter<AudioEvent>,
+    mut commands: Commands,
+) {
+    if versus.phase != RoundPhase::Playing {
+        return;
+    }
+
+    for (entity, player, _) in &player_query {
+        let earned = player.score / ATTACK_COST;
+
+        if earned <= versus.attacks_sent[player.index] {
+            continue;
+        }
+
+        versus.attacks_sent[player.index] = earned;
+
+        if let Some((target, _, target_position)) =
+            player_query.iter().find(|(other, ..)| *other != entity)
+        {
+            commands.spawn(HazardBundle::new(
+                target,
+                Vector2::new(target_position.0.x, 0.0),
+            ));
+            audio_writer.send(AudioEvent::Attack);
+        }
+    }
+}
+
+type DownedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static Player,
+        Option<&'static PlayerControls>,
+        Has<AiController>,
+    ),
+    With<Downed>,
+>;
+
+/// Bring back a downed player when it presses its launch key (right away for
+/// computer players), at the cost of a shared life.
+pub fn coop_revive(
+    mut coop: ResMut<CoopState>,
+    player_query: DownedQuery,
+    input: Res<InputManager>,
+    mut event_writer: EventWriter<GameplayEvent>,
+    mut commands: Commands,
+) {
+    for (entity, position, player, controls, computer) in &player_query {
+        let wants_back =
+            computer || controls.is_some_and(|controls| input.is_key_pressed(controls.launch));
+
+        if coop.lives > 0 && wants_back {
+            coop.lives -= 1;
+            event_writer.send(
+                GameplayEvent::new(GameplayEventKind::LifeLost, Cause::Fall, position.0)
+                    .entity(entity)
+                    .player(player.index),
+            );
+
+            commands.entity(entity).remove::<Downed>();
+            commands.spawn(BallBundle::new(entity, player.ball_color, position));
+        }
+    }
+}
+
+pub fn coop_stage_logic(mut coop: ResMut<CoopState>, brick_query: Query<&BrickKind>) {
+    if !coop.stage_cleared && !brick_query.iter().any(|kind| kind.is_breakable()) {
+        coop.stage_cleared = true;
+    }
+}
@@ -0,0 +1,399 @@
+//! Game in progress, saved when the window is closed and resumed from the menu.
+//!
+//! Solo and cooperative games are written to `savegame.json`, with their
+//! statistics. Level scripts are loaded again on resume, but their variables
+//! are lost. Versus games aren't saved, the menu says so.
+
+use std::{collections::HashMap, fs};
+
+use bevy_ecs::{query::With, world::World};
+use nalgebra::Vector2;
+use raylib::ffi::Color;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ai::{AiController, AiDifficulty},
+    components::{Colored, Position, Size, Velocity},
+    entities::{
+        ball::Ball,
+        brick::{Brick, BrickBundle, BrickKind, Detonating, Sliding},
+        player::{Downed, Player},
+    },
+    level::Level,
+    rules::{CoopState, Difficulty},
+    stats::SessionStats,
+};
+
+const SAVE_PATH: &str = "savegame.json";
+
+/// Bumped when older saves can't be read any more, they are then ignored.
+/// Fields added since are `#[serde(default)]`, so that older saves still load.
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+
+    /// Only present in cooperative mode.
+    pub(super) coop: Option<SavedCoop>,
+
+    pub(super) level: usize,
+    pub(super) levels: Vec<SavedLevel>,
+    pub(super) endless_seed: Option<u64>,
+    pub(super) score: u32,
+    /// Saves of older versions were all played in normal difficulty.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+
+    pub(super) players: Vec<SavedPlayer>,
+    pub(super) balls: Vec<SavedBall>,
+    pub(super) bricks: Vec<SavedBrick>,
+
+    /// Missing from the saves made before the statistics.
+    #[serde(default)]
+    pub(super) stats: Option<SessionStats>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedCoop {
+    lives: u32,
+    stage: u32,
+    started: Vec<bool>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedLevel {
+    name: String,
+    /// See `Level::to_text`.
+    layout: String,
+    script: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedPlayer {
+    pub(super) index: usize,
+    position: [f32; 2],
+    size: [f32; 2],
+    color: [u8; 4],
+    ball_color: [u8; 4],
+    life: i32,
+    score: u32,
+    stunned: u32,
+    /// Missing from the saves made before the combos.
+    #[serde(default)]
+    combo: u32,
+    downed: bool,
+    pub(super) cpu: Option<AiDifficulty>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedBall {
+    /// Index of the owner.
+    player: usize,
+    position: [f32; 2],
+    velocity: [f32; 2],
+    radius: i32,
+    active: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SavedBrick {
+    position: [f32; 2],
+    size: [f32; 2],
+    color: [u8; 4],
+    /// See `BrickKind::to_byte`.
+    kind: u8,
+    /// Path and velocity of a moving brick.
+    sliding: Option<(f32, f32, [f32; 2])>,
+    /// Frames left and index of the player of an exploding brick.
+    detonating: Option<(u32, Option<usize>)>,
+}
+
+fn color_to_array(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+fn color_from_array([r, g, b, a]: [u8; 4]) -> Color {
+    Color { r, g, b, a }
+}
+
+impl SavedLevel {
+    pub(super) fn new(level: &Level) -> Self {
+        Self {
+            name: level.name.clone(),
+            layout: level.to_text(),
+            script: level.script.clone(),
+        }
+    }
+
+    pub(super) fn to_level(&self) -> Option<Level> {
+        let mut level = Level::parse(&self.name, &self.layout)
+            .map_err(|err| eprintln!("Invalid level {} in {SAVE_PATH}: {err}", self.name))
+            .ok()?;
+
+        level.script = self.script.clone();
+        Some(level)
+    }
+}
+
+impl SaveGame {
+    pub fn exists() -> bool {
+        fs::metadata(SAVE_PATH).is_ok()
+    }
+
+    /// Read the save, reporting why it can't be used.
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(SAVE_PATH)
+            .map_err(|err| eprintln!("Unable to read {SAVE_PATH}: {err}"))
+            .ok()?;
+
+        let save: Self = serde_json::from_str(&content)
+            .map_err(|err| eprintln!("Invalid {SAVE_PATH}: {err}"))
+            .ok()?;
+
+        if save.version != SAVE_VERSION {
+            eprintln!(
+                "Ignoring {SAVE_PATH}, written by version {} of the format (expected {SAVE_VERSION})",
+                save.version
+            );
+            return None;
+        }
+
+        Some(save)
+    }
+
+    pub fn write(&self) {
+        let result = serde_json::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|content| fs::write(SAVE_PATH, content).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            eprintln!("Unable to save the game in {SAVE_PATH}: {err}");
+        }
+    }
+
+    /// Forget the save, once resumed.
+    pub fn delete() {
+        fs::remove_file(SAVE_PATH).ok();
+    }
+
+    /// Save the entities of the world.
+    pub(super) fn capture(
+        world: &mut World,
+        level: usize,
+        levels: &[Level],
+        endless_seed: Option<u64>,
+        score: u32,
+        difficulty: Difficulty,
+        stats: Option<SessionStats>,
+    ) -> Self {
+        let coop = world.get_resource::<CoopState>().map(|coop| SavedCoop {
+            lives: coop.lives,
+            stage: coop.stage,
+            started: coop.started.clone(),
+        });
+
+        let players = world
+            .query::<(
+                &Player,
+                &Position,
+                &Size,
+                &Colored,
+                Option<&Downed>,
+                Option<&AiController>,
+            )>()
+            .iter(world)
+            .map(|(player, position, size, color, downed, ai)| SavedPlayer {
+                index: player.index,
+                position: position.0.into(),
+                size: size.0.into(),
+                color: color_to_array(color.0),
+                ball_color: color_to_array(player.ball_color),
+                life: player.life,
+                score: player.score,
+                stunned: player.stunned,
+                combo: player.combo,
+                downed: downed.is_some(),
+                cpu: ai.map(|ai| ai.difficulty),
+            })
+            .collect();
+
+        let balls = world
+            .query::<(&Ball, &Position, &Velocity)>()
+            .iter(world)
+            .filter_map(|(ball, position, velocity)| {
+                Some(SavedBall {
+                    player: world.get::<Player>(ball.owner)?.index,
+                    position: position.0.into(),
+                    velocity: velocity.0.into(),
+                    radius: ball.radius,
+                    active: ball.active,
+                })
+            })
+            .collect();
+
+        let bricks = world
+            .query_filtered::<(
+                &Position,
+                &Size,
+                &Colored,
+                &BrickKind,
+                Option<(&Sliding, &Velocity)>,
+                Option<&Detonating>,
+            ), With<Brick>>()
+            .iter(world)
+            .map(
+                |(position, size, color, kind, sliding, detonating)| SavedBrick {
+                    position: position.0.into(),
+                    size: size.0.into(),
+                    color: color_to_array(color.0),
+                    kind: kind.to_byte(),
+                    sliding: sliding
+                        .map(|(sliding, velocity)| (sliding.min, sliding.max, velocity.0.into())),
+                    detonating: detonating.map(|detonating| {
+                        let player = detonating
+                            .player
+                            .and_then(|player| world.get::<Player>(player));
+
+                        (detonating.timer, player.map(|player| player.index))
+                    }),
+                },
+            )
+            .collect();
+
+        Self {
+            version: SAVE_VERSION,
+            coop,
+            level,
+            levels: levels.iter().map(SavedLevel::new).collect(),
+            endless_seed,
+            score,
+            difficulty,
+            players,
+            balls,
+            bricks,
+            stats,
+        }
+    }
+
+    /// Spawn the saved entities in the world.
+    pub(super) fn restore(&self, world: &mut World) {
+        if let Some(coop) = &self.coop {
+            let mut state = CoopState::new(coop.started.len());
+            state.lives = coop.lives;
+            state.stage = coop.stage;
+            state.started = coop.started.clone();
+
+            world.insert_resource(state);
+        }
+
+        let mut players = HashMap::new();
+
+        for saved in &self.players {
+            let mut entity = world.spawn((
+                Position(saved.position.into()),
+                Size(saved.size.into()),
+                Player {
+                    index: saved.index,
+                    life: saved.life,
+                    score: saved.score,
+                    ball_color: color_from_array(saved.ball_color),
+                    stunned: saved.stunned,
+                    combo: saved.combo,
+                },
+                Colored(color_from_array(saved.color)),
+            ));
+
+            match saved.cpu {
+                Some(difficulty) => entity.insert(AiController::new(difficulty)),
+                None => entity.insert(super::player_controls(saved.index)),
+            };
+
+            if saved.downed {
+                entity.insert(Downed);
+            }
+
+            players.insert(saved.index, entity.id());
+        }
+
+        for saved in &self.balls {
+            let Some(&owner) = players.get(&saved.player) else {
+                continue;
+            };
+
+            let color = world.get::<Player>(owner).map(|player| player.ball_color);
+
+            world.spawn((
+                Position(saved.position.into()),
+                Velocity(saved.velocity.into()),
+                Ball {
+                    radius: saved.radius,
+                    active: saved.active,
+                    owner,
+                },
+                Colored(color.unwrap_or(Color::MAROON)),
+            ));
+        }
+
+        for saved in &self.bricks {
+            let mut entity = world.spawn(BrickBundle {
+                position: Position(saved.position.into()),
+                size: Size(saved.size.into()),
+                color: Colored(color_from_array(saved.color)),
+                kind: BrickKind::from_byte(saved.kind).unwrap_or_default(),
+                brick: Brick,
+            });
+
+            if let Some((min, max, velocity)) = saved.sliding {
+                entity.insert((Sliding { min, max }, Velocity(Vector2::from(velocity))));
+            }
+
+            // The brick still explodes if its player is gone, scoring for nobody.
+            if let Some((timer, player)) = saved.detonating {
+                let player = player.and_then(|player| players.get(&player).copied());
+                entity.insert(Detonating { timer, player });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+
+    use super::*;
+    use crate::game::bench::{build_world, Scenario};
+
+    #[test]
+    fn bricks_of_a_gone_player_still_explode() {
+        let (mut world, player) = build_world(&Scenario {
+            bricks: 1,
+            balls: 0,
+            ticks: 0,
+        });
+
+        let brick = world.query_filtered::<Entity, With<Brick>>().single(&world);
+        world.entity_mut(brick).insert(Detonating {
+            timer: 3,
+            player: Some(player),
+        });
+        world.despawn(player);
+
+        let save = SaveGame::capture(
+            &mut world,
+            0,
+            &[Level::default()],
+            None,
+            0,
+            Difficulty::Normal,
+            None,
+        );
+
+        let mut restored = World::new();
+        save.restore(&mut restored);
+
+        let detonating = restored.query::<&Detonating>().single(&restored);
+        assert_eq!(detonating.timer, 3);
+        assert!(detonating.player.is_none());
+    }
+}
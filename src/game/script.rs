@@ -0,0 +1,217 @@
+//! Level scripts, written in Rhai.
+//!
+//! A level may come with a script defining any of these hooks:
+//! - `on_stage_start(stage)`
+//! - `on_brick_destroyed(player, column, row)`
+//! - `on_ball_lost(player)`
+//!
+//! The top level of the script runs once, when the stage starts, and not again
+//! when a saved game is resumed. Like any Rhai function, the hooks can't read
+//! the variables it declares.
+//! Scripts never touch the world directly: the functions they may call (see
+//! `register_api`) queue `ScriptCommand`s, applied by the game once the hook returns.
+
+use std::{cell::RefCell, rc::Rc};
+
+use rhai::{
+    module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST,
+};
+
+/// Operations a hook may run, so that a buggy script can't freeze the game.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Change to the game requested by a script.
+pub enum ScriptCommand {
+    SpawnBrick {
+        column: usize,
+        row: usize,
+    },
+    RemoveBrick {
+        column: usize,
+        row: usize,
+    },
+    AddScore {
+        player: usize,
+        points: u32,
+    },
+    AddLife {
+        player: usize,
+    },
+    /// Launch an extra ball from the paddle of a player.
+    SpawnBall {
+        player: usize,
+    },
+    /// Multiply the speed of every ball.
+    ScaleBallSpeed(f32),
+}
+
+type CommandQueue = Rc<RefCell<Vec<ScriptCommand>>>;
+
+pub struct LevelScript {
+    name: String,
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    commands: CommandQueue,
+}
+
+impl LevelScript {
+    /// Compile the script of a level, reporting errors.
+    pub fn load(name: &str, source: &str) -> Option<Self> {
+        let commands = CommandQueue::default();
+
+        let mut engine = Engine::new();
+        // No access to the file system.
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        register_api(&mut engine, &commands);
+
+        let ast = engine
+            .compile(source)
+            .map_err(|err| eprintln!("Script of level {name}: {err}"))
+            .ok()?;
+
+        Some(Self {
+            name: name.to_string(),
+            engine,
+            ast,
+            scope: Scope::new(),
+            commands,
+        })
+    }
+
+    /// Run the top level statements of the script, reporting errors. The
+    /// commands they queue come with the next hook.
+    pub fn start(mut self) -> Option<Self> {
+        self.engine
+            .run_ast_with_scope(&mut self.scope, &self.ast)
+            .map_err(|err| eprintln!("Script of level {}: {err}", self.name))
+            .ok()?;
+
+        Some(self)
+    }
+
+    /// Run a hook if the script defines it, returns the commands queued since the last call.
+    pub fn call(&mut self, hook: &str, args: impl FuncArgs) -> Vec<ScriptCommand> {
+        if self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == hook)
+        {
+            // The top level already ran in `start`.
+            let options = CallFnOptions::new().eval_ast(false).rewind_scope(false);
+
+            if let Err(err) = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut self.scope,
+                &self.ast,
+                hook,
+                args,
+            ) {
+                eprintln!("Script of level {}, {hook}: {err}", self.name);
+            }
+        }
+
+        self.commands.borrow_mut().drain(..).collect()
+    }
+}
+
+/// Negative values from scripts are ignored.
+fn index(value: i64) -> Option<usize> {
+    usize::try_from(value).ok()
+}
+
+fn register_api(engine: &mut Engine, commands: &CommandQueue) {
+    let queue = commands.clone();
+    engine.register_fn("spawn_brick", move |column: i64, row: i64| {
+        if let (Some(column), Some(row)) = (index(column), index(row)) {
+            queue
+                .borrow_mut()
+                .push(ScriptCommand::SpawnBrick { column, row });
+        }
+    });
+
+    let queue = commands.clone();
+    engine.register_fn("remove_brick", move |column: i64, row: i64| {
+        if let (Some(column), Some(row)) = (index(column), index(row)) {
+            queue
+                .borrow_mut()
+                .push(ScriptCommand::RemoveBrick { column, row });
+        }
+    });
+
+    let queue = commands.clone();
+    engine.register_fn("add_score", move |player: i64, points: i64| {
+        if let (Some(player), Ok(points)) = (index(player), u32::try_from(points)) {
+            queue
+                .borrow_mut()
+                .push(ScriptCommand::AddScore { player, points });
+        }
+    });
+
+    let queue = commands.clone();
+    engine.register_fn("add_life", move |player: i64| {
+        if let Some(player) = index(player) {
+            queue.borrow_mut().push(ScriptCommand::AddLife { player });
+        }
+    });
+
+    let queue = commands.clone();
+    engine.register_fn("spawn_ball", move |player: i64| {
+        if let Some(player) = index(player) {
+            queue.borrow_mut().push(ScriptCommand::SpawnBall { player });
+        }
+    });
+
+    let queue = commands.clone();
+    engine.register_fn("scale_ball_speed", move |factor: f64| {
+        // Keep the balls playable.
+        let factor = (factor as f32).clamp(0.25, 4.0);
+        queue
+            .borrow_mut()
+            .push(ScriptCommand::ScaleBallSpeed(factor));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_dont_run_the_top_level_again() {
+        let source = "add_score(0, 1);\nfn on_ball_lost(player) { add_score(player, 2); }";
+        let mut script = LevelScript::load("test", source)
+            .and_then(LevelScript::start)
+            .unwrap();
+
+        // Commands of the top level come with the first hook.
+        assert_eq!(script.call("on_stage_start", (1_i64,)).len(), 1);
+
+        for _ in 0..2 {
+            let commands = script.call("on_ball_lost", (0_i64,));
+
+            assert!(matches!(
+                commands[..],
+                [ScriptCommand::AddScore {
+                    player: 0,
+                    points: 2
+                }]
+            ));
+        }
+    }
+
+    #[test]
+    fn resumed_scripts_dont_run_the_top_level() {
+        let source = "add_score(0, 1);\nfn on_ball_lost(player) { add_life(player); }";
+        let mut script = LevelScript::load("test", source).unwrap();
+
+        let commands = script.call("on_ball_lost", (0_i64,));
+
+        assert!(matches!(
+            commands[..],
+            [ScriptCommand::AddLife { player: 0 }]
+        ));
+    }
+}
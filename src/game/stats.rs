@@ -0,0 +1,122 @@
+//! Statistics of a game, shown once it is over and appended to `stats.jsonl`.
+//! They are kept in the save of a game left unfinished.
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    events::{GameplayEvent, GameplayEventKind},
+    rules::{Difficulty, GameMode},
+};
+
+const STATS_PATH: &str = "stats.jsonl";
+
+/// Steps of the game per second.
+const STEPS_PER_SECOND: u32 = 60;
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct PlayerStats {
+    pub bricks: u32,
+    pub paddle_hits: u32,
+    pub balls_lost: u32,
+    /// Most paddle hits in a row without losing a ball.
+    pub longest_rally: u32,
+    #[serde(skip)]
+    rally: u32,
+}
+
+impl PlayerStats {
+    /// Share of the balls coming down that were sent back.
+    pub fn accuracy(&self) -> f32 {
+        match self.paddle_hits + self.balls_lost {
+            0 => 0.0,
+            total => self.paddle_hits as f32 / total as f32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionStats {
+    mode: String,
+    difficulty: String,
+    /// Steps played, pauses between rounds and GAME OVER screens excluded.
+    steps: u32,
+    pub players: Vec<PlayerStats>,
+}
+
+impl SessionStats {
+    pub fn new(mode: GameMode, difficulty: Difficulty) -> Self {
+        Self {
+            mode: mode.id().to_string(),
+            difficulty: difficulty.id().to_string(),
+            steps: 0,
+            players: vec![PlayerStats::default(); mode.player_count()],
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.steps += 1;
+    }
+
+    pub fn seconds(&self) -> u32 {
+        self.steps / STEPS_PER_SECOND
+    }
+
+    /// Count a gameplay event for the player concerned.
+    pub fn record(&mut self, event: &GameplayEvent) {
+        let Some(stats) = event.player.and_then(|player| self.players.get_mut(player)) else {
+            return;
+        };
+
+        match event.kind {
+            GameplayEventKind::BrickDestroyed => stats.bricks += 1,
+            GameplayEventKind::PaddleHit => {
+                stats.paddle_hits += 1;
+                stats.rally += 1;
+                stats.longest_rally = stats.longest_rally.max(stats.rally);
+            }
+            GameplayEventKind::BallLost => {
+                stats.balls_lost += 1;
+                stats.rally = 0;
+            }
+            _ => (),
+        }
+    }
+
+    /// Add the statistics as a line of the stats file, with the time the game ended.
+    pub fn append(&self) {
+        #[derive(Serialize)]
+        struct Record<'a> {
+            ended_at: u64,
+            #[serde(flatten)]
+            stats: &'a SessionStats,
+        }
+
+        let ended_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        let result = serde_json::to_string(&Record {
+            ended_at,
+            stats: self,
+        })
+        .map_err(|err| err.to_string())
+        .and_then(|line| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(STATS_PATH)
+                .and_then(|mut file| writeln!(file, "{line}"))
+                .map_err(|err| err.to_string())
+        });
+
+        if let Err(err) = result {
+            eprintln!("Unable to write the statistics in {STATS_PATH}: {err}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs;
+
+use raylib::{
+    core::{drawing::RaylibDrawHandle, text::measure_text},
+    ffi::Color,
+    prelude::RaylibDraw,
+};
+
+use crate::game::rules::Difficulty;
+
+const HIGH_SCORES_PATH: &str = "highscores.txt";
+
+/// Number of scores kept in the table.
+const MAX_HIGH_SCORES: usize = 10;
+
+struct HighScore {
+    score: u32,
+    difficulty: Difficulty,
+}
+
+impl HighScore {
+    /// Read a `<score> <difficulty>` line, scores written before the
+    /// difficulties were added are normal ones.
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let score = words.next()?.parse().ok()?;
+
+        let difficulty = match words.next() {
+            Some(id) => Difficulty::from_id(id)?,
+            None => Difficulty::Normal,
+        };
+
+        Some(Self { score, difficulty })
+    }
+}
+
+/// Best solo scores, highest first.
+#[derive(Default)]
+pub struct HighScores(Vec<HighScore>);
+
+impl HighScores {
+    pub fn load() -> Self {
+        let mut scores: Vec<HighScore> = fs::read_to_string(HIGH_SCORES_PATH)
+            .map(|content| content.lines().filter_map(HighScore::parse).collect())
+            .unwrap_or_default();
+
+        scores.sort_by_key(|best| std::cmp::Reverse(best.score));
+        scores.truncate(MAX_HIGH_SCORES);
+
+        Self(scores)
+    }
+
+    pub fn save(&self) {
+        let content: String = self
+            .0
+            .iter()
+            .map(|best| format!("{} {}\n", best.score, best.difficulty.id()))
+            .collect();
+
+        if let Err(err) = fs::write(HIGH_SCORES_PATH, content) {
+            eprintln!("Unable to save the high scores: {err}");
+        }
+    }
+
+    /// Add a score to the table, returns false if it isn't good enough to be kept.
+    pub fn insert(&mut self, score: u32, difficulty: Difficulty) -> bool {
+        let rank = self
+            .0
+            .iter()
+            .position(|best| score > best.score)
+            .unwrap_or(self.0.len());
+
+        if rank >= MAX_HIGH_SCORES {
+            return false;
+        }
+
+        self.0.insert(rank, HighScore { score, difficulty });
+        self.0.truncate(MAX_HIGH_SCORES);
+        true
+    }
+
+    pub fn draw(&self, d: &RaylibDrawHandle, width: i32, height: i32) {
+        let title = "Meilleurs scores";
+
+        d.draw_text(
+            title,
+            (width - measure_text(title, 48)) / 2,
+            height / 8,
+            48,
+            Color::BLACK,
+        );
+
+        if self.0.is_empty() {
+            let text = "Aucun score pour le moment";
+
+            d.draw_text(
+                text,
+                (width - measure_text(text, 24)) / 2,
+                height / 2,
+                24,
+                Color::DARKGRAY,
+            );
+        }
+
+        for (i, best) in self.0.iter().enumerate() {
+            let line = format!(
+                "{:>2}.  {:>6}  {}",
+                i + 1,
+                best.score,
+                best.difficulty.name()
+            );
+
+            d.draw_text(
+                &line,
+                (width - measure_text(&line, 28)) / 2,
+                height / 4 + 40 * i as i32,
+                28,
+                Color::DARKGRAY,
+            );
+        }
+    }
+}
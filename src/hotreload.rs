@@ -0,0 +1,91 @@
+//! Development mode, started with `--dev`.
+//!
+//! The intro is skipped and the asset and mod directories are polled: when a
+//! file is added, removed or modified, the mods (levels included) and the assets
+//! are loaded again without restarting the game.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Time between two looks at the files.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Take `--dev` out of `args`.
+pub fn dev_from_args(args: &mut Vec<String>) -> bool {
+    let Some(index) = args.iter().position(|arg| arg == "--dev") else {
+        return false;
+    };
+
+    args.remove(index);
+    true
+}
+
+/// Last modification of every file of the directories.
+fn scan(dir: &Path, stamps: &mut HashMap<PathBuf, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            scan(&path, stamps);
+        } else if let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) {
+            stamps.insert(path, modified);
+        }
+    }
+}
+
+pub struct AssetWatcher {
+    dirs: Vec<PathBuf>,
+    stamps: HashMap<PathBuf, SystemTime>,
+    last_poll: Instant,
+}
+
+impl AssetWatcher {
+    pub fn new(dirs: Vec<PathBuf>) -> Self {
+        let mut stamps = HashMap::new();
+        dirs.iter().for_each(|dir| scan(dir, &mut stamps));
+
+        Self {
+            dirs,
+            stamps,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Whether a file changed since the last call, the files are only looked
+    /// at every `POLL_INTERVAL`.
+    pub fn changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+
+        self.last_poll = Instant::now();
+
+        let mut stamps = HashMap::new();
+        self.dirs.iter().for_each(|dir| scan(dir, &mut stamps));
+
+        let mut changed: Vec<&PathBuf> = stamps
+            .keys()
+            .chain(self.stamps.keys())
+            .filter(|path| stamps.get(*path) != self.stamps.get(*path))
+            .collect();
+
+        changed.sort();
+        changed.dedup();
+
+        for path in &changed {
+            eprintln!("{} changed, reloading", path.display());
+        }
+
+        let changed = !changed.is_empty();
+        self.stamps = stamps;
+        changed
+    }
+}
@@ -3,11 +3,19 @@ use raylib::{
     ffi::Color,
 };
 
-pub fn intro(rl: &RaylibHandle) {
+use crate::{assets::Assets, credits::Credits, meta::GameInfo};
+
+/// Credits of the libraries, shown after the authors of the game.
+const LIBRARY_CREDITS: &str =
+    "raylib-rs par DeltaPHC, Mia Ayeke, Teddy Astie\nraylib par Raymon Santamaria et al.";
+
+/// Show the logos, returns false if a key was pressed (or a coin inserted) to skip them.
+pub fn intro(rl: &RaylibHandle, info: &GameInfo, assets: &Assets, credits: &mut Credits) -> bool {
     let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+    let authors = format!("{}\n\n{LIBRARY_CREDITS}", info.authors.join("\n"));
 
-    let logo_raylib = rl.load_texture("assets/logo_raylib.png").unwrap();
-    let logo_raylib_rs = rl.load_texture("assets/logo_raylib_rust.png").unwrap();
+    let logo_raylib = assets.texture("logo_raylib");
+    let logo_raylib_rs = assets.texture("logo_raylib_rust");
 
     let mut time = 0.0;
 
@@ -26,36 +34,44 @@ pub fn intro(rl: &RaylibHandle) {
             d.clear_background(Color::WHITE);
 
             d.draw_text(
-                "Polykanoid",
-                (w - logo_raylib.as_raw().width * 2) / 2 + 40,
-                (h - logo_raylib.as_raw().height) / 2 - 80,
+                &info.title,
+                (w - logo_raylib.width() * 2) / 2 + 40,
+                (h - logo_raylib.height()) / 2 - 80,
                 36,
                 Color::BLACK.fade(opacity),
             );
 
-            d.draw_texture(
-                &logo_raylib,
-                (w - logo_raylib.as_raw().width * 2) / 2 - 16,
-                (h - logo_raylib.as_raw().height) / 2,
+            logo_raylib.draw(
+                &d,
+                (w - logo_raylib.width() * 2) / 2 - 16,
+                (h - logo_raylib.height()) / 2,
                 Color::WHITE.fade(opacity),
             );
 
-            d.draw_texture(
-                &logo_raylib_rs,
+            logo_raylib_rs.draw(
+                &d,
                 w / 2 + 16,
-                (h - logo_raylib_rs.as_raw().height) / 2,
+                (h - logo_raylib_rs.height()) / 2,
                 Color::WHITE.fade(opacity),
             );
 
-            //d.draw_text(
-            //    "David \"Dacode45\" Ayeke (original)\nTeddy Astie (améliorations, sons, raylib 5.0, adaptation P++)\n\nraylib-rs par DeltaPHC, Mia Ayeke, Teddy Astie\nraylib par Raymon Santamaria et al.",
-            //    (w - logo_raylib.as_raw().width * 2) / 2 - 16,
-            //    (h + logo_raylib.as_raw().height) / 2 + 16,
-            //    10,
-            //    Color::BLACK.fade(opacity),
-            //);
+            d.draw_text(
+                &authors,
+                (w - logo_raylib.width() * 2) / 2 - 16,
+                (h + logo_raylib.height()) / 2 + 16,
+                10,
+                Color::BLACK.fade(opacity),
+            );
         });
 
+        let coin = credits.update(rl);
+
+        if rl.get_key_pressed().is_some() || coin {
+            return false;
+        }
+
         time += rl.get_frame_time();
     }
+
+    true
 }
@@ -1,70 +1,494 @@
-use assets::Assets;
-use game::Game;
+use achievements::Achievements;
+use assets::{Assets, SoundBank};
+use credits::{CreditSettings, Credits};
+use game::{
+    ai::AiDifficulty,
+    bench, generator,
+    net::{Client, Host, NetOptions},
+    palette::Palette,
+    rules::{Difficulty, GameMode, WinCondition},
+    save::SaveGame,
+    Game,
+};
+use highscores::HighScores;
+use hotreload::AssetWatcher;
+use meta::GameInfo;
+use mods::Mods;
 use raylib::{
-    core::drawing::RaylibDraw,
+    core::{drawing::RaylibDraw, text::measure_text},
     ffi::{Color, KeyboardKey},
     prelude::{RaylibAudio, RaylibHandle},
 };
 
+mod about;
+mod achievements;
 mod assets;
+mod attract;
+mod credits;
+mod editor;
 mod game;
+mod highscores;
+mod hotreload;
 mod intro;
+mod meta;
+mod mods;
+mod results;
+mod theme;
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let net_options = NetOptions::from_args(&mut args);
+    let mut credits = Credits::new(CreditSettings::from_args(&mut args));
+    let edit_path = editor::path_from_args(&mut args);
+    let bench_scenarios = bench::scenarios_from_args(&mut args);
+    let dev = hotreload::dev_from_args(&mut args);
+
+    for arg in args {
+        eprintln!("Ignoring unknown argument {arg}");
+    }
+
+    // Runs without a window.
+    if let Some(scenarios) = bench_scenarios {
+        scenarios.iter().for_each(bench::run);
+        return;
+    }
+
+    let info = GameInfo::load();
+    let mut mods = Mods::load(&info.mods);
+
     let rl = raylib::init()
-        .title("Polykanoid")
+        .title(&info.title)
         .width(1366)
         .height(768)
         .fullscreen()
         .vsync()
         .build();
 
-    intro::intro(&rl);
-
     let raudio = RaylibAudio::init_audio_device();
 
     rl.set_target_fps(60);
     raudio.set_master_volume(0.4);
 
-    let mut game = Game::default();
-    let assets = Assets::load(&raudio);
+    let mut assets = Assets::load(&rl, &raudio, &mods, info.theme.as_deref());
+    let mut watcher = dev.then(|| AssetWatcher::new(Mods::dirs(&info.mods)));
+
+    if !dev {
+        intro::intro(&rl, &info, &assets, &mut credits);
+    }
 
-    let two_players = main_menu(&rl);
+    let mut highscores = HighScores::load();
+    let mut achievements = Achievements::load(&mods);
 
-    if rl.window_should_close() {
+    if let Some(path) = edit_path {
+        editor::editor(&rl, &raudio, &assets, &mods, &path);
         return;
     }
-
-    game.init(&rl, two_players);
+    let mut settings = MenuSettings::default();
 
     while !rl.window_should_close() {
-        game.update(&rl, &raudio, &assets);
-        rl.begin_drawing(|d| game.draw(&rl, &d));
+        let mut game = Game::default();
+
+        match net_options {
+            Some(NetOptions::Join { host, conditions }) => {
+                match Client::connect(host, conditions) {
+                    Ok(client) => game.init_client(&rl, client),
+                    Err(err) => {
+                        eprintln!("Unable to join {host}: {err}");
+                        return;
+                    }
+                }
+            }
+            _ => {
+                game.set_levels(mods.levels.clone());
+
+                let mode = match main_menu(&rl, &assets, &mut credits, &mut settings, &mut watcher)
+                {
+                    MenuChoice::Play(mode) => Some(mode),
+                    MenuChoice::Endless => {
+                        game.set_endless(fastrand::u64(..));
+                        Some(GameMode::Solo)
+                    }
+                    MenuChoice::Daily => {
+                        game.set_levels(vec![generator::daily_level()]);
+                        Some(GameMode::Solo)
+                    }
+                    MenuChoice::VersusCpu => {
+                        game.set_cpu_player(1, settings.cpu_difficulty);
+                        Some(GameMode::Versus)
+                    }
+                    MenuChoice::Resume => None,
+                    MenuChoice::Reload => {
+                        mods = Mods::load(&info.mods);
+                        assets = Assets::load(&rl, &raudio, &mods, info.theme.as_deref());
+                        continue;
+                    }
+                    MenuChoice::About => {
+                        about::about_screen(&rl, &info);
+                        continue;
+                    }
+                    MenuChoice::Attract => {
+                        attract::attract_mode(
+                            &rl,
+                            &raudio,
+                            &assets,
+                            &info,
+                            &mods,
+                            &highscores,
+                            &mut credits,
+                        );
+                        continue;
+                    }
+                };
+
+                if rl.window_should_close() {
+                    return;
+                }
+
+                assets.play(&raudio, SoundBank::Ui);
+                game.set_win_condition(settings.win_condition);
+                game.set_palette(settings.palette);
+
+                let save = mode.is_none().then(SaveGame::load).flatten();
+
+                // A resumed game goes on in the difficulty it was started with.
+                let difficulty = save
+                    .as_ref()
+                    .map_or(settings.difficulty, |save| save.difficulty);
+
+                game.set_difficulty(difficulty);
+                game.set_tuning(mods.tuning(difficulty.tuning()));
+
+                match (mode, save) {
+                    (Some(mode), _) => game.init(&rl, mode),
+                    (None, Some(save)) => {
+                        SaveGame::delete();
+                        game.resume(&rl, save);
+                    }
+                    (None, None) => continue,
+                }
+
+                if let Some(NetOptions::Host { port, conditions }) = net_options {
+                    match Host::bind(port, conditions) {
+                        Ok(host) => game.set_host(host),
+                        Err(err) => eprintln!("Unable to host on port {port}: {err}"),
+                    }
+                }
+            }
+        }
+
+        achievements.start_game();
+
+        while !rl.window_should_close() && !game.is_over() {
+            credits.update(&rl);
+
+            if game.can_continue()
+                && rl.is_key_pressed(KeyboardKey::KEY_Z)
+                && credits.continue_game()
+            {
+                game.continue_game();
+            }
+
+            // Sounds can be changed during a game, levels are only read again
+            // for the next one.
+            if watcher.as_mut().is_some_and(AssetWatcher::changed) {
+                mods = Mods::load(&info.mods);
+                assets = Assets::load(&rl, &raudio, &mods, info.theme.as_deref());
+            }
+
+            game.update(&rl, &raudio, &assets);
+
+            for event in game.take_events() {
+                achievements.record(&event);
+            }
+
+            achievements.update(rl.get_frame_time());
+
+            rl.begin_drawing(|d| {
+                game.draw(&d, &assets);
+                achievements.draw(&d, rl.get_screen_width());
+
+                if game.can_continue() {
+                    let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+
+                    credits.draw(&d, w, h, Color::WHITE);
+
+                    if credits.continues() {
+                        let text = "Appuyez sur Z pour continuer";
+
+                        d.draw_text(
+                            text,
+                            (w - measure_text(text, 24)) / 2,
+                            h / 2 + 40,
+                            24,
+                            Color::WHITE,
+                        );
+                    }
+                }
+            });
+        }
+
+        // Keep the run for the next time.
+        if rl.window_should_close() {
+            if let Some(save) = game.save() {
+                save.write();
+            }
+        }
+
+        achievements.save();
+
+        if let Some(score) = game.final_score() {
+            if highscores.insert(score, game.difficulty()) {
+                highscores.save();
+            }
+        }
+
+        if let Some(results) = game.results() {
+            results::results_screen(&rl, &results);
+        }
+
+        // Games left by closing the window are saved, their statistics will
+        // be counted once they are over.
+        if let Some(stats) = game.stats().filter(|_| game.is_over()) {
+            stats.append();
+            results::stats_screen(&rl, stats);
+        }
     }
 }
 
-fn main_menu(rl: &RaylibHandle) -> bool {
-    let logo1p = rl.load_texture("assets/logo1j.png").unwrap();
-    let logo2p = rl.load_texture("assets/logo2j.png").unwrap();
+/// Seconds the "insert coin" message stays after trying to play without credits.
+const INSERT_COIN_DURATION: f32 = 2.0;
+
+enum MenuChoice {
+    Play(GameMode),
+    /// Solo game on generated levels that never end.
+    Endless,
+    /// Solo game on the generated level of the day.
+    Daily,
+    /// Versus against a computer controlled paddle.
+    VersusCpu,
+    /// Credits and description of the game.
+    About,
+    /// Nobody touched the cabinet for a while.
+    Attract,
+    /// Go on with the game saved when the cabinet was closed.
+    Resume,
+    /// Files of the assets changed, in development mode.
+    Reload,
+}
+
+/// Choices of the menu, kept from a game to the next.
+struct MenuSettings {
+    difficulty: Difficulty,
+    cpu_difficulty: AiDifficulty,
+    win_condition: WinCondition,
+    palette: Palette,
+}
+
+impl Default for MenuSettings {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            cpu_difficulty: AiDifficulty::Normal,
+            win_condition: WinCondition::LastStanding,
+            palette: Palette::Standard,
+        }
+    }
+}
+
+/// Move `current` to the next entry of `choices`, going back to the first one
+/// after the last one.
+fn cycle_next<T: Copy + PartialEq>(choices: &[T], current: &mut T) {
+    let index = choices.iter().position(|choice| choice == current);
+    *current = choices[index.map_or(0, |index| (index + 1) % choices.len())];
+}
+
+/// Move `current` to the previous or next entry of `choices` with the arrow keys.
+fn cycle<T: Copy + PartialEq>(
+    rl: &RaylibHandle,
+    choices: &[T],
+    current: &mut T,
+    previous: KeyboardKey,
+    next: KeyboardKey,
+) {
+    let index = choices
+        .iter()
+        .position(|choice| choice == current)
+        .unwrap_or_default();
+
+    if rl.is_key_pressed(previous) {
+        *current = choices[index.saturating_sub(1)];
+    }
+
+    if rl.is_key_pressed(next) {
+        *current = choices[(index + 1).min(choices.len() - 1)];
+    }
+}
+
+fn main_menu(
+    rl: &RaylibHandle,
+    assets: &Assets,
+    credits: &mut Credits,
+    settings: &mut MenuSettings,
+    watcher: &mut Option<AssetWatcher>,
+) -> MenuChoice {
+    let logo1p = assets.texture("logo_1p");
+    let logo2p = assets.texture("logo_2p");
+
+    let saved = SaveGame::exists();
+
+    let mut idle_time = 0.0;
+
+    // Time left showing that a coin is needed.
+    let mut insert_coin_time = 0.0;
 
     while !rl.window_should_close() {
         rl.begin_drawing(|d| {
             d.clear_background(Color::WHITE);
 
-            d.draw_text("Mode 1 joueur", 400, 200, 32, Color::BLACK);
-            d.draw_texture(&logo1p, 200, 200 - 50, Color::WHITE);
-            d.draw_text("Mode 2 joueur", 400, 450, 32, Color::BLACK);
-            d.draw_texture(&logo2p, 200, 450 - 50, Color::WHITE);
+            if saved {
+                d.draw_text(
+                    "K : reprendre la partie en cours",
+                    400,
+                    20,
+                    24,
+                    Color::DARKGREEN,
+                );
+            }
+
+            d.draw_text(
+                &format!("D : difficulté {}", settings.difficulty.name()),
+                900,
+                20,
+                20,
+                Color::DARKGRAY,
+            );
+            d.draw_text(
+                &format!("P : couleurs {}", settings.palette.name()),
+                900,
+                45,
+                20,
+                Color::DARKGRAY,
+            );
+
+            d.draw_text("Mode 1 joueur", 400, 120, 32, Color::BLACK);
+            logo1p.draw(&d, 200, 120 - 50, Color::WHITE);
+            d.draw_text(
+                "N : sans fin  -  J : niveau du jour",
+                400,
+                160,
+                20,
+                Color::DARKGRAY,
+            );
+            d.draw_text("Mode versus", 400, 280, 32, Color::BLACK);
+            logo2p.draw(&d, 200, 280 - 50, Color::WHITE);
+            d.draw_text(
+                &format!("< {} >", settings.win_condition.name()),
+                400,
+                320,
+                20,
+                Color::DARKGRAY,
+            );
+            d.draw_text(
+                "Les duels ne sont pas sauvegardés en quittant",
+                400,
+                345,
+                16,
+                Color::GRAY,
+            );
+            d.draw_text("Mode coopératif", 400, 440, 32, Color::BLACK);
+            logo2p.draw(&d, 200, 440 - 50, Color::WHITE);
+            d.draw_text("Versus contre l'ordinateur", 400, 600, 32, Color::BLACK);
+            logo1p.draw(&d, 200, 600 - 50, Color::WHITE);
+            d.draw_text(
+                &format!("< {} >", settings.cpu_difficulty.name()),
+                400,
+                640,
+                20,
+                Color::DARKGRAY,
+            );
+
+            credits.draw(
+                &d,
+                rl.get_screen_width(),
+                rl.get_screen_height(),
+                Color::BLACK,
+            );
+
+            if insert_coin_time > 0.0 {
+                d.draw_text("Insérez une pièce", 400, 700, 32, Color::MAROON);
+            }
         });
 
-        if rl.is_key_down(KeyboardKey::KEY_Z) {
-            return false;
+        let coin = credits.update(rl);
+
+        if rl.get_key_pressed().is_some() || coin {
+            idle_time = 0.0;
+        } else {
+            idle_time += rl.get_frame_time();
+        }
+
+        if idle_time >= attract::ATTRACT_DELAY {
+            return MenuChoice::Attract;
+        }
+
+        if watcher.as_mut().is_some_and(AssetWatcher::changed) {
+            return MenuChoice::Reload;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            return MenuChoice::About;
         }
 
-        if rl.is_key_down(KeyboardKey::KEY_X) {
-            return true;
+        // Already paid for.
+        if saved && rl.is_key_pressed(KeyboardKey::KEY_K) {
+            return MenuChoice::Resume;
+        }
+
+        // Pick the rule of versus matches and the difficulty of the computer.
+        cycle(
+            rl,
+            &WinCondition::ALL,
+            &mut settings.win_condition,
+            KeyboardKey::KEY_UP,
+            KeyboardKey::KEY_DOWN,
+        );
+        cycle(
+            rl,
+            &AiDifficulty::ALL,
+            &mut settings.cpu_difficulty,
+            KeyboardKey::KEY_LEFT,
+            KeyboardKey::KEY_RIGHT,
+        );
+
+        if rl.is_key_pressed(KeyboardKey::KEY_D) {
+            cycle_next(&Difficulty::ALL, &mut settings.difficulty);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_P) {
+            cycle_next(&Palette::ALL, &mut settings.palette);
+        }
+
+        insert_coin_time = (insert_coin_time - rl.get_frame_time()).max(0.0);
+
+        let choice = [
+            (KeyboardKey::KEY_Z, MenuChoice::Play(GameMode::Solo)),
+            (KeyboardKey::KEY_X, MenuChoice::Play(GameMode::Versus)),
+            (KeyboardKey::KEY_C, MenuChoice::Play(GameMode::Coop)),
+            (KeyboardKey::KEY_V, MenuChoice::VersusCpu),
+            (KeyboardKey::KEY_N, MenuChoice::Endless),
+            (KeyboardKey::KEY_J, MenuChoice::Daily),
+        ]
+        .into_iter()
+        .find(|(key, _)| rl.is_key_pressed(*key));
+
+        if let Some((_, choice)) = choice {
+            if credits.start_game() {
+                return choice;
+            }
+
+            insert_coin_time = INSERT_COIN_DURATION;
         }
     }
 
-    false
+    MenuChoice::Play(GameMode::Solo)
 }
@@ -0,0 +1,67 @@
+use std::fs;
+
+use serde::Deserialize;
+
+const GAME_INFO_PATH: &str = "meta/game.json";
+
+/// Cartridge metadata, read from `meta/game.json`.
+#[derive(Deserialize)]
+pub struct GameInfo {
+    pub title: String,
+    pub creation_date: String,
+    pub description: String,
+    pub authors: Vec<String>,
+    pub tags: Vec<String>,
+
+    /// Mods to load, in order.
+    #[serde(default)]
+    pub mods: Vec<String>,
+
+    /// Sprites in `assets/themes/`, flat shapes if unset.
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+impl Default for GameInfo {
+    fn default() -> Self {
+        Self {
+            title: "Polykanoid".to_string(),
+            creation_date: String::new(),
+            description: String::new(),
+            authors: vec![],
+            tags: vec![],
+            mods: vec![],
+            theme: None,
+        }
+    }
+}
+
+impl GameInfo {
+    /// Read the manifest, falling back to the defaults (and saying why) if it is
+    /// missing or invalid.
+    pub fn load() -> Self {
+        let content = match fs::read_to_string(GAME_INFO_PATH) {
+            Ok(content) => content,
+            Err(err) => {
+                eprintln!("Unable to read {GAME_INFO_PATH}: {err}");
+                return Self::default();
+            }
+        };
+
+        match serde_json::from_str::<GameInfo>(&content) {
+            Ok(info) if info.title.trim().is_empty() => {
+                eprintln!("Invalid {GAME_INFO_PATH}: `title` is empty");
+                Self {
+                    title: Self::default().title,
+                    ..info
+                }
+            }
+            Ok(info) => info,
+            Err(err) => {
+                // serde_json errors already tell the field and the line/column.
+                eprintln!("Invalid {GAME_INFO_PATH}: {err}");
+                Self::default()
+            }
+        }
+    }
+}
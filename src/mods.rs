@@ -0,0 +1,206 @@
+//! Mods listed in `meta/game.json`, loaded in order from `mods/<name>/`.
+//!
+//! A mod directory may contain:
+//! - `assets/`: files replacing the game assets of the same name, including
+//!   `manifest.json` to change the sound banks and textures,
+//! - `levels/`: a level pack, `.txt` levels played after the ones already loaded,
+//!   each one may come with a `.rhai` script of the same name,
+//! - `mod.json`: gameplay constants to tweak, such as `{ "ball_speed": 12.0 }`.
+//!
+//! When several mods change the same asset or constant, the last one wins and
+//! the conflict is reported.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    assets::AssetManifest,
+    game::{level::Level, rules::Tuning},
+};
+
+const MODS_DIR: &str = "mods";
+const ASSETS_DIR: &str = "assets";
+
+/// Constants a mod may change, see `Tuning`.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+struct ModManifest {
+    ball_speed: Option<f32>,
+    player_speed: Option<f32>,
+    player_lives: Option<i32>,
+}
+
+pub struct Mods {
+    /// Replaced assets (by file name) with the mod replacing them.
+    assets: HashMap<String, (PathBuf, String)>,
+
+    /// Levels to play, the default one first.
+    pub levels: Vec<Level>,
+
+    /// Constants changed by the mods, over the ones of the difficulty.
+    tuning: ModManifest,
+
+    /// Sounds and textures, read once the replaced assets are known.
+    pub manifest: AssetManifest,
+}
+
+impl Default for Mods {
+    fn default() -> Self {
+        Self {
+            assets: HashMap::new(),
+            levels: vec![Level::default()],
+            tuning: ModManifest::default(),
+            manifest: AssetManifest::default(),
+        }
+    }
+}
+
+impl Mods {
+    pub fn load(names: &[String]) -> Self {
+        let mut mods = Self::default();
+
+        // Mod changing each constant.
+        let mut constants: HashMap<&str, &str> = HashMap::new();
+
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                eprintln!("Mod {name} is listed twice, ignoring the second one");
+                continue;
+            }
+
+            let dir = Path::new(MODS_DIR).join(name);
+
+            if !dir.is_dir() {
+                eprintln!("Mod {name} not found (expected in {})", dir.display());
+                continue;
+            }
+
+            mods.load_assets(name, &dir.join(ASSETS_DIR));
+            mods.load_levels(name, &dir.join("levels"));
+
+            let Some(manifest) = read_manifest(name, &dir.join("mod.json")) else {
+                continue;
+            };
+
+            let changes = [
+                ("ball_speed", manifest.ball_speed.is_some()),
+                ("player_speed", manifest.player_speed.is_some()),
+                ("player_lives", manifest.player_lives.is_some()),
+            ];
+
+            for (constant, _) in changes.into_iter().filter(|(_, changed)| *changed) {
+                if let Some(previous) = constants.insert(constant, name) {
+                    eprintln!("Mod {name} changes {constant}, also changed by mod {previous}");
+                }
+            }
+
+            let tuning = &mut mods.tuning;
+            tuning.ball_speed = manifest.ball_speed.or(tuning.ball_speed);
+            tuning.player_speed = manifest.player_speed.or(tuning.player_speed);
+            tuning.player_lives = manifest.player_lives.or(tuning.player_lives);
+        }
+
+        mods.manifest = AssetManifest::load(&mods);
+        mods
+    }
+
+    /// Constants of a difficulty with the changes of the mods.
+    pub fn tuning(&self, base: Tuning) -> Tuning {
+        Tuning {
+            ball_speed: self.tuning.ball_speed.unwrap_or(base.ball_speed),
+            player_speed: self.tuning.player_speed.unwrap_or(base.player_speed),
+            player_lives: self.tuning.player_lives.unwrap_or(base.player_lives),
+            ..base
+        }
+    }
+
+    /// Directories the files of the assets and of the mods are read from.
+    pub fn dirs(names: &[String]) -> Vec<PathBuf> {
+        let mods = names.iter().map(|name| Path::new(MODS_DIR).join(name));
+        [PathBuf::from(ASSETS_DIR)]
+            .into_iter()
+            .chain(mods)
+            .collect()
+    }
+
+    /// Path of an asset, taking the mods into account.
+    pub fn asset(&self, name: &str) -> String {
+        match self.assets.get(name) {
+            Some((path, _)) => path.to_string_lossy().into_owned(),
+            None => format!("{ASSETS_DIR}/{name}"),
+        }
+    }
+
+    fn load_assets(&mut self, mod_name: &str, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if !Path::new(ASSETS_DIR).join(file_name).exists() {
+                eprintln!("Mod {mod_name}: {file_name} doesn't replace any asset");
+            }
+
+            let file_name = file_name.to_string();
+
+            if let Some((_, previous)) = self
+                .assets
+                .insert(file_name.clone(), (path, mod_name.to_string()))
+            {
+                eprintln!("Mod {mod_name} replaces {file_name}, also replaced by mod {previous}");
+            }
+        }
+    }
+
+    fn load_levels(&mut self, mod_name: &str, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+
+        // Levels are played in the order of their file names.
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+            let level = fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| Level::parse(&format!("{mod_name}/{name}"), &text));
+
+            match level {
+                Ok(mut level) => {
+                    level.script = fs::read_to_string(path.with_extension("rhai")).ok();
+                    self.levels.push(level);
+                }
+                Err(err) => eprintln!("Mod {mod_name}: invalid level {}: {err}", path.display()),
+            }
+        }
+    }
+}
+
+fn read_manifest(mod_name: &str, path: &Path) -> Option<ModManifest> {
+    let content = fs::read_to_string(path).ok()?;
+
+    match serde_json::from_str(&content) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            eprintln!("Mod {mod_name}: invalid {}: {err}", path.display());
+            None
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use raylib::{
+    core::{
+        drawing::{RaylibDraw, RaylibDrawHandle},
+        text::measure_text,
+        RaylibHandle,
+    },
+    ffi::{Color, KeyboardKey},
+};
+
+use crate::game::{
+    rules::MatchResults,
+    stats::{PlayerStats, SessionStats},
+};
+
+/// Minimum time the results stay on screen, so that a player still
+/// hammering the launch key doesn't skip them.
+const MIN_DISPLAY_TIME: f32 = 2.0;
+
+/// Show a screen until Z or X is pressed, once it has stayed `MIN_DISPLAY_TIME`.
+fn show(rl: &RaylibHandle, draw: impl Fn(&RaylibDrawHandle, i32, i32)) {
+    let (w, h) = (rl.get_screen_width(), rl.get_screen_height());
+    let mut time = 0.0;
+
+    while !rl.window_should_close() {
+        rl.begin_drawing(|d| {
+            d.clear_background(Color::WHITE);
+            draw(&d, w, h);
+
+            if time >= MIN_DISPLAY_TIME {
+                let hint = "Appuyez sur Z ou X pour continuer";
+
+                d.draw_text(
+                    hint,
+                    (w - measure_text(hint, 20)) / 2,
+                    h - 80,
+                    20,
+                    Color::GRAY,
+                );
+            }
+        });
+
+        if time >= MIN_DISPLAY_TIME
+            && (rl.is_key_pressed(KeyboardKey::KEY_Z) || rl.is_key_pressed(KeyboardKey::KEY_X))
+        {
+            return;
+        }
+
+        time += rl.get_frame_time();
+    }
+}
+
+pub fn results_screen(rl: &RaylibHandle, results: &MatchResults) {
+    let title = match results.winner {
+        Some(winner) => format!("Victoire du joueur {} !", winner + 1),
+        None => "Égalité".to_string(),
+    };
+
+    show(rl, |d, w, h| {
+        d.draw_text(
+            &title,
+            (w - measure_text(&title, 48)) / 2,
+            h / 4,
+            48,
+            Color::BLACK,
+        );
+
+        for (i, (wins, score)) in results.wins.iter().zip(&results.scores).enumerate() {
+            let line = format!("Joueur {} : {wins} manche(s), {score} points", i + 1);
+
+            d.draw_text(
+                &line,
+                (w - measure_text(&line, 24)) / 2,
+                h / 2 + 40 * i as i32,
+                24,
+                Color::DARKGRAY,
+            );
+        }
+    });
+}
+
+/// Label of a statistic and how to get it from the ones of a player.
+type StatRow = (&'static str, fn(&PlayerStats) -> String);
+
+/// Statistics of each player at the end of a game, one column per player.
+pub fn stats_screen(rl: &RaylibHandle, stats: &SessionStats) {
+    let rows: [StatRow; 5] = [
+        ("Briques cassées", |player| player.bricks.to_string()),
+        ("Renvois", |player| player.paddle_hits.to_string()),
+        ("Balles perdues", |player| player.balls_lost.to_string()),
+        ("Plus long échange", |player| {
+            player.longest_rally.to_string()
+        }),
+        ("Précision", |player| {
+            format!("{:.0} %", player.accuracy() * 100.0)
+        }),
+    ];
+
+    let seconds = stats.seconds();
+    let title = format!("Temps de jeu : {}:{:02}", seconds / 60, seconds % 60);
+
+    show(rl, |d, w, h| {
+        d.draw_text(
+            &title,
+            (w - measure_text(&title, 48)) / 2,
+            h / 6,
+            48,
+            Color::BLACK,
+        );
+
+        let (label_x, column_width) = (w / 2 - 360, 240);
+        let top = h / 3;
+
+        for (i, _) in stats.players.iter().enumerate() {
+            d.draw_text(
+                &format!("Joueur {}", i + 1),
+                label_x + 320 + column_width * i as i32,
+                top,
+                24,
+                Color::BLACK,
+            );
+        }
+
+        for (row, (label, value)) in rows.iter().enumerate() {
+            let y = top + 50 + 40 * row as i32;
+
+            d.draw_text(label, label_x, y, 24, Color::DARKGRAY);
+
+            for (i, player) in stats.players.iter().enumerate() {
+                d.draw_text(
+                    &value(player),
+                    label_x + 320 + column_width * i as i32,
+                    y,
+                    24,
+                    Color::DARKGRAY,
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,171 @@
+//! Sprites of the bricks, paddles and balls, read from
+//! `assets/themes/<name>/theme.json` for the theme set in `meta/game.json`:
+//!
+//! ```json
+//! {
+//!     "bricks": { "normal": "brick.png", "steel": "steel.png" },
+//!     "paddle": "paddle.png",
+//!     "paddle_cap": 12,
+//!     "ball": "ball.png"
+//! }
+//! ```
+//!
+//! Sprites are tinted with the colour of what they stand for, so they should
+//! be drawn in white. The ends of the paddle (`paddle_cap` pixels wide) keep
+//! their size, only its middle is stretched. Anything a theme lacks is drawn
+//! with the flat shapes, which is the default theme.
+
+use std::fs;
+
+use nalgebra::Vector2;
+use raylib::{
+    core::{
+        drawing::{RaylibDraw, RaylibDrawHandle},
+        texture::Texture2D,
+        RaylibHandle,
+    },
+    ffi::{Color, Rectangle},
+};
+use serde::Deserialize;
+
+use crate::{assets::AssetError, game::level::BrickKind, mods::Mods};
+
+const THEMES_DIR: &str = "themes";
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct BrickSprites {
+    normal: Option<String>,
+    explosive: Option<String>,
+    steel: Option<String>,
+    moving: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ThemeManifest {
+    #[serde(default)]
+    bricks: BrickSprites,
+    paddle: Option<String>,
+    #[serde(default)]
+    paddle_cap: f32,
+    ball: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Theme {
+    /// Indexed by `BrickKind::to_byte`.
+    bricks: [Option<Texture2D>; 4],
+    paddle: Option<Texture2D>,
+    paddle_cap: f32,
+    ball: Option<Texture2D>,
+}
+
+impl Theme {
+    /// Load the sprites of a theme, the flat one if `name` is `None`.
+    pub fn load(rl: &RaylibHandle, mods: &Mods, name: Option<&str>) -> Self {
+        let Some(name) = name else {
+            return Self::default();
+        };
+
+        let dir = format!("{THEMES_DIR}/{name}");
+        let path = mods.asset(&format!("{dir}/theme.json"));
+
+        let manifest = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<ThemeManifest>(&content).map_err(|err| err.to_string())
+            })
+            .map_err(|reason| AssetError::Manifest { path, reason });
+
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!("{err}, using the flat theme");
+                return Self::default();
+            }
+        };
+
+        let load = |file: &Option<String>| {
+            let path = mods.asset(&format!("{dir}/{}", file.as_ref()?));
+
+            rl.load_texture(&path)
+                .map_err(|reason| AssetError::Load {
+                    path,
+                    reason: reason.to_string(),
+                })
+                .map_err(|err| eprintln!("{err}, drawing it flat"))
+                .ok()
+        };
+
+        let bricks = &manifest.bricks;
+
+        Self {
+            bricks: [
+                load(&bricks.normal),
+                load(&bricks.explosive),
+                load(&bricks.steel),
+                load(&bricks.moving),
+            ],
+            paddle: load(&manifest.paddle),
+            paddle_cap: manifest.paddle_cap,
+            ball: load(&manifest.ball),
+        }
+    }
+
+    pub fn brick(&self, kind: BrickKind) -> Option<&Texture2D> {
+        self.bricks[kind.to_byte() as usize].as_ref()
+    }
+
+    /// Sprite of the paddles with the width of its ends.
+    pub fn paddle(&self) -> Option<(&Texture2D, f32)> {
+        Some((self.paddle.as_ref()?, self.paddle_cap))
+    }
+
+    pub fn ball(&self) -> Option<&Texture2D> {
+        self.ball.as_ref()
+    }
+}
+
+fn texture_rect(texture: &Texture2D, x: f32, width: f32) -> Rectangle {
+    Rectangle::new(x, 0.0, width, texture.as_raw().height as f32)
+}
+
+/// Draw a whole sprite stretched over `dest`.
+pub fn draw_sprite(d: &RaylibDrawHandle, texture: &Texture2D, dest: Rectangle, tint: Color) {
+    let source = texture_rect(texture, 0.0, texture.as_raw().width as f32);
+    d.draw_texture_pro(texture, source, dest, Vector2::zeros(), 0.0, tint);
+}
+
+/// Draw a sprite over `dest`, stretching only its middle so that its ends,
+/// `cap` pixels wide, keep their shape.
+pub fn draw_sliced(
+    d: &RaylibDrawHandle,
+    texture: &Texture2D,
+    cap: f32,
+    dest: Rectangle,
+    tint: Color,
+) {
+    let width = texture.as_raw().width as f32;
+    // Ends too large for the sprite or the destination are shrunk.
+    let cap = cap.min(width / 2.0).min(dest.width / 2.0).max(0.0);
+
+    let slices = [
+        (texture_rect(texture, 0.0, cap), dest.x, cap),
+        (
+            texture_rect(texture, cap, width - 2.0 * cap),
+            dest.x + cap,
+            dest.width - 2.0 * cap,
+        ),
+        (
+            texture_rect(texture, width - cap, cap),
+            dest.x + dest.width - cap,
+            cap,
+        ),
+    ];
+
+    for (source, x, w) in slices {
+        let slice = Rectangle::new(x, dest.y, w, dest.height);
+        d.draw_texture_pro(texture, source, slice, Vector2::zeros(), 0.0, tint);
+    }
+}